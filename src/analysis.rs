@@ -0,0 +1,561 @@
+
+use crate::Function;
+use crate::linalg::determinant;
+use crate::sample::{grid, sample};
+
+pub fn estimate_period(f: &Function, search_max: f64, samples: usize) -> Option<f64> {
+    let samples = samples.max(2);
+    let step = search_max / samples as f64;
+    // offset the window so symmetric zeros (like sin at 0 and pi) don't look periodic
+    let window: Vec<f64> = (0..samples).map(|k| 0.1234567 + k as f64 * step).collect();
+    let values: Vec<f64> = window.iter().map(|&x| f.eval(x)).collect();
+    let scale = values.iter().filter(|v| v.is_finite()).fold(1.0, |acc: f64, v| acc.max(v.abs()));
+    let mismatch = |period: f64| {
+        window.iter().zip(&values)
+            .map(|(&x, &y)| (f.eval(x + period) - y).abs())
+            .filter(|d| d.is_finite())
+            .fold(0.0, f64::max) / scale
+    };
+
+    let candidates: Vec<f64> = (0..=samples + 1).map(|j| j as f64 * step).collect();
+    let errors: Vec<f64> = candidates.iter().map(|&t| mismatch(t)).collect();
+    for j in 1..=samples {
+        if candidates[j] > search_max {
+            break;
+        }
+        if errors[j] > errors[j - 1] || errors[j] > errors[j + 1] {
+            continue;
+        }
+        // ternary search for the bottom of the dip around the candidate
+        let (mut lo, mut hi) = (candidates[j - 1], candidates[j + 1]);
+        while hi - lo > 1e-12 {
+            let m1 = lo + (hi - lo) / 3.0;
+            let m2 = hi - (hi - lo) / 3.0;
+            if mismatch(m1) < mismatch(m2) {
+                hi = m2;
+            } else {
+                lo = m1;
+            }
+        }
+        let period = (lo + hi) / 2.0;
+        if period > 0.0 && period <= search_max && mismatch(period) < 1e-6 {
+            return Some(period);
+        }
+    }
+    None
+}
+
+pub fn limit(f: &Function, c: f64) -> Option<f64> {
+    let (left, right) = (limit_left(f, c)?, limit_right(f, c)?);
+    if left == right {
+        Some(left)
+    } else if left.is_infinite() || right.is_infinite() {
+        None
+    } else if (left - right).abs() <= 1e-6 * (1.0 + left.abs()) {
+        Some((left + right) / 2.0)
+    } else {
+        None
+    }
+}
+
+pub fn limit_left(f: &Function, c: f64) -> Option<f64> {
+    one_sided_limit(f, c, -1.0)
+}
+
+pub fn limit_right(f: &Function, c: f64) -> Option<f64> {
+    one_sided_limit(f, c, 1.0)
+}
+
+fn one_sided_limit(f: &Function, c: f64, direction: f64) -> Option<f64> {
+    let values: Vec<f64> = (1..=8).map(|k| f.eval(c + direction * 10f64.powi(-k))).collect();
+    let tail = &values[values.len() - 4..];
+    let last = *tail.last()?;
+    if last.is_infinite() {
+        return Some(last);
+    }
+    if tail.iter().any(|v| !v.is_finite()) {
+        return None;
+    }
+    let steps: Vec<f64> = tail.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+    if steps.windows(2).all(|s| s[1] <= s[0]) && steps[steps.len() - 1] <= 1e-6 * (1.0 + last.abs()) {
+        return Some(last);
+    }
+    // growing without settling, always on the same side of zero, means the limit is infinite
+    let same_sign = tail.iter().all(|v| v.signum() == last.signum());
+    let growing = tail.windows(2).all(|w| w[1].abs() > w[0].abs());
+    let not_slowing = steps.windows(2).all(|s| s[1] >= 0.99 * s[0]);
+    if same_sign && growing && not_slowing {
+        return Some(last.signum() * f64::INFINITY);
+    }
+    None
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Stability {
+    Attracting,
+    Repelling,
+    Neutral,
+}
+
+pub fn roots_in(f: &Function, a: f64, b: f64, samples: usize) -> Vec<f64> {
+    let points = sample(f, a, b, samples.max(2));
+    let mut roots: Vec<f64> = vec![];
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if y0 == 0.0 {
+            roots.push(x0);
+        } else if y0.is_finite() && y1.is_finite() && y0.signum() != y1.signum() && y1 != 0.0 {
+            let root = bisect(f, x0, x1);
+            // a sign flip across a pole is not a root
+            if f.eval(root).abs() < 1e-6 {
+                roots.push(root);
+            }
+        }
+    }
+    if let Some(&(x, y)) = points.last() {
+        if y == 0.0 {
+            roots.push(x);
+        }
+    }
+    roots
+}
+
+fn bisect(f: &Function, mut lo: f64, mut hi: f64) -> f64 {
+    let low_sign = f.eval(lo).signum();
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        let value = f.eval(mid);
+        if value == 0.0 {
+            return mid;
+        }
+        if value.signum() == low_sign {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub type Intervals = Vec<(f64, f64)>;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct ShapeReport {
+    pub critical_points: Vec<f64>,
+    pub local_maxima: Vec<f64>,
+    pub local_minima: Vec<f64>,
+    pub inflection_points: Vec<f64>,
+    pub increasing: Intervals,
+    pub decreasing: Intervals,
+    pub concave_up: Intervals,
+    pub concave_down: Intervals,
+}
+
+pub fn shape_analysis(f: &Function, a: f64, b: f64, samples: usize) -> ShapeReport {
+    let first = f.prime();
+    let second = first.prime();
+    let critical_points = roots_in(&first, a, b, samples);
+    let (increasing, decreasing) = sign_intervals(&first, a, b, &critical_points);
+    let candidates = roots_in(&second, a, b, samples);
+    let (concave_up, concave_down) = sign_intervals(&second, a, b, &candidates);
+    let turns = |c: f64, from: &[(f64, f64)], to: &[(f64, f64)]| {
+        from.iter().any(|&(_, end)| end == c) && to.iter().any(|&(start, _)| start == c)
+    };
+    ShapeReport {
+        local_maxima: critical_points.iter().copied().filter(|&c| turns(c, &increasing, &decreasing)).collect(),
+        local_minima: critical_points.iter().copied().filter(|&c| turns(c, &decreasing, &increasing)).collect(),
+        // a zero of f'' is only an inflection point if the concavity actually changes there
+        inflection_points: candidates.into_iter()
+            .filter(|&c| turns(c, &concave_up, &concave_down) || turns(c, &concave_down, &concave_up))
+            .collect(),
+        critical_points,
+        increasing,
+        decreasing,
+        concave_up,
+        concave_down,
+    }
+}
+
+// splits [a, b] at the cuts and merges neighbouring pieces where g has the same sign
+fn sign_intervals(g: &Function, a: f64, b: f64, cuts: &[f64]) -> (Intervals, Intervals) {
+    let mut bounds = vec![a];
+    bounds.extend(cuts.iter().filter(|&&c| c > a && c < b));
+    bounds.push(b);
+    let (mut positive, mut negative): (Intervals, Intervals) = (vec![], vec![]);
+    for pair in bounds.windows(2) {
+        let value = g.eval((pair[0] + pair[1]) / 2.0);
+        let side = if value > 0.0 {
+            &mut positive
+        } else if value < 0.0 {
+            &mut negative
+        } else {
+            continue;
+        };
+        match side.last_mut() {
+            Some(last) if last.1 == pair[0] => last.1 = pair[1],
+            _ => side.push((pair[0], pair[1])),
+        }
+    }
+    (positive, negative)
+}
+
+pub fn fixed_points(f: &Function, a: f64, b: f64, samples: usize) -> Vec<f64> {
+    roots_in(&(f - Function::X), a, b, samples)
+}
+
+pub fn fixed_point_stability(f: &Function, x: f64) -> Stability {
+    let slope = f.prime().eval(x).abs();
+    if (slope - 1.0).abs() < 1e-9 {
+        Stability::Neutral
+    } else if slope < 1.0 {
+        Stability::Attracting
+    } else {
+        Stability::Repelling
+    }
+}
+
+pub fn lipschitz_estimate(f: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    let slope = f.prime();
+    grid(a, b, samples).into_iter().map(|x| slope.eval(x).abs()).fold(0.0, f64::max)
+}
+
+pub fn tangent_error_bound(f: &Function, x0: f64, x: f64, samples: usize) -> f64 {
+    let curvature = f.prime().prime();
+    let max = grid(x0, x, samples).into_iter().map(|t| curvature.eval(t).abs()).fold(0.0, f64::max);
+    max * (x - x0).powi(2) / 2.0
+}
+
+pub fn osculating_circle(f: &Function, x0: f64) -> Option<(f64, f64, f64)> {
+    let slope = f.prime();
+    let (y, dy, ddy) = (f.eval(x0), slope.eval(x0), slope.prime().eval(x0));
+    // zero curvature has no finite circle
+    if ddy == 0.0 || !ddy.is_finite() {
+        return None;
+    }
+    let stretch = 1.0 + dy * dy;
+    Some((x0 - dy * stretch / ddy, y + stretch / ddy, stretch.powf(1.5) / ddy.abs()))
+}
+
+pub fn difference_quotient(f: &Function, x0: f64) -> Function {
+    (f.compose(&(Function::X + x0)) - f.eval(x0)) / Function::X
+}
+
+pub fn central_difference_function(f: &Function, h: f64) -> Function {
+    (f.compose(&(Function::X + h)) - f.compose(&(Function::X - h))) / (2.0 * h)
+}
+
+pub fn wronskian(fs: &[Function], x: f64) -> f64 {
+    determinant(wronskian_matrix(&derivative_rows(fs), x))
+}
+
+fn derivative_rows(fs: &[Function]) -> Vec<Vec<Function>> {
+    let mut rows = vec![fs.to_vec()];
+    for _ in 1..fs.len() {
+        let next = rows[rows.len() - 1].iter().map(Function::prime).collect();
+        rows.push(next);
+    }
+    rows
+}
+
+fn wronskian_matrix(rows: &[Vec<Function>], x: f64) -> Vec<Vec<f64>> {
+    rows.iter().map(|row| row.iter().map(|f| f.eval(x)).collect()).collect()
+}
+
+pub fn linearly_independent(fs: &[Function], a: f64, b: f64, samples: usize) -> bool {
+    let rows = derivative_rows(fs);
+    grid(a, b, samples).into_iter().any(|x| {
+        let matrix = wronskian_matrix(&rows, x);
+        // compare against Hadamard's bound so the tolerance scales with the entries
+        let bound: f64 = matrix.iter().map(|row| row.iter().map(|v| v * v).sum::<f64>().sqrt()).product();
+        let det = determinant(matrix);
+        det.is_finite() && det.abs() > 1e-9 * bound
+    })
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum IndeterminateForm {
+    ZeroOverZero,
+    InfOverInf,
+    ZeroTimesInf,
+    InfMinusInf,
+    OneToInf,
+    ZeroToZero,
+    InfToZero,
+}
+
+const ODE_MAX_ORDER: usize = 4;
+
+impl Function {
+    pub fn indeterminate_form_at(&self, x: f64) -> Option<IndeterminateForm> {
+        let (a, b) = match self {
+            Self::Constant(_) | Self::X => return None,
+            Self::Add(a, b) | Self::Subtract(a, b) | Self::Multiply(a, b) | Self::Divide(a, b) | Self::Pow(a, b) => {
+                if let Some(form) = a.indeterminate_form_at(x).or_else(|| b.indeterminate_form_at(x)) {
+                    return Some(form);
+                }
+                (a.eval(x), b.eval(x))
+            }
+            Self::Powi(a, _) | Self::Powa(_, a) | Self::Sqrt(a) | Self::Exp(a) | Self::Ln(a)
+                | Self::Sin(a) | Self::Cos(a) | Self::Tan(a) | Self::Asin(a) | Self::Acos(a) | Self::Atan(a)
+                | Self::Sinh(a) | Self::Cosh(a) | Self::Tanh(a) | Self::Abs(a) | Self::Erf(a) => {
+                return a.indeterminate_form_at(x)
+            }
+        };
+        match self {
+            Self::Divide(_, _) if a == 0.0 && b == 0.0 => Some(IndeterminateForm::ZeroOverZero),
+            Self::Divide(_, _) if a.is_infinite() && b.is_infinite() => Some(IndeterminateForm::InfOverInf),
+            Self::Multiply(_, _) if (a == 0.0 && b.is_infinite()) || (a.is_infinite() && b == 0.0) => {
+                Some(IndeterminateForm::ZeroTimesInf)
+            }
+            Self::Add(_, _) if a.is_infinite() && b == -a => Some(IndeterminateForm::InfMinusInf),
+            Self::Subtract(_, _) if a.is_infinite() && b == a => Some(IndeterminateForm::InfMinusInf),
+            Self::Pow(_, _) if a == 1.0 && b.is_infinite() => Some(IndeterminateForm::OneToInf),
+            Self::Pow(_, _) if a == 0.0 && b == 0.0 => Some(IndeterminateForm::ZeroToZero),
+            Self::Pow(_, _) if a.is_infinite() && b == 0.0 => Some(IndeterminateForm::InfToZero),
+            _ => None,
+        }
+    }
+
+    pub fn find_root(&self, initial_guess: f64, tolerance: f64, max_iterations: u64) -> Option<f64> {
+        let slope = self.prime();
+        let mut x = initial_guess;
+        for _ in 0..max_iterations {
+            let (value, derivative) = (self.eval(x), slope.eval(x));
+            if !value.is_finite() || !derivative.is_finite() || derivative == 0.0 {
+                return None;
+            }
+            let step = value / derivative;
+            x -= step;
+            if !x.is_finite() {
+                return None;
+            }
+            if step.abs() <= tolerance {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    // the residual receives [y, y', ..., y^(ODE_MAX_ORDER)]
+    pub fn satisfies_ode(&self, residual: impl Fn(&[Function]) -> Function, samples: &[f64], tol: f64) -> bool {
+        let mut derivatives = vec![self.clone()];
+        for n in 0..ODE_MAX_ORDER {
+            let next = derivatives[n].derivative();
+            derivatives.push(next);
+        }
+        let residual = residual(&derivatives);
+        samples.iter().all(|&x| residual.eval(x).abs() <= tol)
+    }
+
+    pub fn symmetric_derivative(&self, x: f64, h: f64) -> f64 {
+        (self.eval(x + h) - self.eval(x - h)) / (2.0 * h)
+    }
+
+    // the central difference, meant for checking prime() against
+    pub fn numerical_derivative(&self, x: f64, h: f64) -> f64 {
+        self.symmetric_derivative(x, h)
+    }
+
+    // a corner or cusp shows up as one-sided slopes that disagree
+    pub fn is_differentiable_at(&self, x: f64, h: f64, tol: f64) -> bool {
+        let value = self.eval(x);
+        let left = (value - self.eval(x - h)) / h;
+        let right = (self.eval(x + h) - value) / h;
+        left.is_finite() && right.is_finite() && (left - right).abs() <= tol
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn sin_has_period_two_pi() {
+        let period = estimate_period(&X.sin(), 10.0, 200).unwrap();
+        assert!((period - 2.0 * PI).abs() < 1e-6, "{period}");
+    }
+
+    #[test]
+    fn sin_squared_has_period_pi() {
+        let period = estimate_period(&X.sin().powf(2.0), 10.0, 200).unwrap();
+        assert!((period - PI).abs() < 1e-6, "{period}");
+    }
+
+    #[test]
+    fn exp_has_no_period() {
+        assert_eq!(estimate_period(&X.exp(), 10.0, 200), None);
+    }
+
+    #[test]
+    fn reciprocal_has_infinite_one_sided_limits_at_zero() {
+        let f = 1.0 / X;
+        assert_eq!(limit_left(&f, 0.0), Some(f64::NEG_INFINITY));
+        assert_eq!(limit_right(&f, 0.0), Some(f64::INFINITY));
+        assert_eq!(limit(&f, 0.0), None);
+    }
+
+    #[test]
+    fn removable_singularity_has_a_limit() {
+        let limit = limit(&(X.sin() / X), 0.0).unwrap();
+        assert!((limit - 1.0).abs() < 1e-9, "{limit}");
+    }
+
+    #[test]
+    fn fixed_point_of_a_contraction_is_attracting() {
+        let f = 0.5 * X + 1.0;
+        let points = fixed_points(&f, -10.0, 10.0, 100);
+        assert_eq!(points.len(), 1);
+        assert!((points[0] - 2.0).abs() < 1e-12, "{points:?}");
+        assert_eq!(fixed_point_stability(&f, points[0]), Stability::Attracting);
+        assert_eq!(fixed_point_stability(&(3.0 * X), 0.0), Stability::Repelling);
+    }
+
+    #[test]
+    fn wronskian_detects_linear_independence() {
+        assert!(linearly_independent(&[X.sin(), X.cos()], -1.0, 1.0, 20));
+        assert!(!linearly_independent(&[X, 2.0 * X], -1.0, 1.0, 20));
+        assert!(linearly_independent(&[Function::from(1.0), X, X.powf(2.0)], -1.0, 1.0, 20));
+        assert!((wronskian(&[X.sin(), X.cos()], 0.7) + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn indeterminate_forms_are_reported() {
+        assert_eq!((X.sin() / X).indeterminate_form_at(0.0), Some(IndeterminateForm::ZeroOverZero));
+        assert_eq!((X * (1.0 / X)).indeterminate_form_at(0.0), Some(IndeterminateForm::ZeroTimesInf));
+        assert_eq!((X.sin() / X).indeterminate_form_at(1.0), None);
+    }
+
+    #[test]
+    fn difference_quotient_approaches_the_derivative() {
+        let quotient = difference_quotient(&X.powf(2.0), 1.0);
+        // (1 + h)^2 - 1 over h is exactly 2 + h
+        for h in [1e-1, 1e-3, 1e-6] {
+            assert!((quotient.eval(h) - 2.0).abs() <= 1.01 * h, "at {h}");
+        }
+    }
+
+    #[test]
+    fn exp_and_sin_satisfy_their_odes() {
+        let samples = [-1.0, 0.0, 0.5, 2.0];
+        assert!(X.exp().satisfies_ode(|y| &y[1] - &y[0], &samples, 1e-12));
+        assert!(X.sin().satisfies_ode(|y| &y[2] + &y[0], &samples, 1e-12));
+        assert!(!X.cos().satisfies_ode(|y| &y[1] - &y[0], &samples, 1e-12));
+    }
+
+    #[test]
+    fn lipschitz_estimate_is_the_largest_slope() {
+        let estimate = lipschitz_estimate(&X.sin(), 0.0, 2.0 * PI, 101);
+        assert!((estimate - 1.0).abs() < 1e-12, "{estimate}");
+        assert_eq!(lipschitz_estimate(&(2.0 * X), -1.0, 1.0, 10), 2.0);
+    }
+
+    #[test]
+    fn central_difference_function_approaches_prime() {
+        let f = X.sin() * X.exp();
+        let exact = f.prime().eval(0.8);
+        let errors: Vec<f64> = [1e-1, 1e-2, 1e-3]
+            .iter()
+            .map(|&h| (central_difference_function(&f, h).eval(0.8) - exact).abs())
+            .collect();
+        assert!(errors.windows(2).all(|w| w[1] < w[0] / 50.0), "{errors:?}");
+    }
+
+    #[test]
+    fn tangent_error_bound_covers_the_actual_error() {
+        let f = X.exp();
+        for x in [-0.5, 0.1, 0.5] {
+            let tangent = 1.0 + x;
+            let bound = tangent_error_bound(&f, 0.0, x, 50);
+            assert!((x.exp() - tangent).abs() <= bound, "at {x}");
+        }
+    }
+
+    #[test]
+    fn shape_of_a_cubic() {
+        let report = shape_analysis(&(X.powf(3.0) - 3.0 * X), -3.0, 3.0, 60);
+        let close = |found: &[f64], expected: &[f64]| {
+            found.len() == expected.len() && found.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-9)
+        };
+        assert!(close(&report.local_maxima, &[-1.0]), "{:?}", report.local_maxima);
+        assert!(close(&report.local_minima, &[1.0]), "{:?}", report.local_minima);
+        assert!(close(&report.inflection_points, &[0.0]), "{:?}", report.inflection_points);
+        assert_eq!(report.increasing.len(), 2);
+        assert_eq!(report.decreasing.len(), 1);
+        assert!(close(&[report.concave_down[0].1, report.concave_up[0].0], &[0.0, 0.0]));
+    }
+
+    #[test]
+    fn newton_converges_to_a_root_or_gives_up() {
+        let root = X.cos().find_root(1.5, 1e-12, 20).unwrap();
+        assert!((root - PI / 2.0).abs() < 1e-12, "{root}");
+        // no real root, a flat start and a step that overshoots further every time
+        assert_eq!((X.powf(2.0) + 1.0).find_root(0.5, 1e-12, 100), None);
+        assert_eq!(X.cos().find_root(0.0, 1e-12, 20), None);
+        assert_eq!(X.atan().find_root(2.0, 1e-12, 100), None);
+    }
+
+    #[test]
+    fn osculating_circle_of_a_parabola_at_its_vertex() {
+        let (cx, cy, radius) = osculating_circle(&X.powf(2.0), 0.0).unwrap();
+        assert!(cx.abs() < 1e-12 && (cy - 0.5).abs() < 1e-12 && (radius - 0.5).abs() < 1e-12);
+        assert_eq!(osculating_circle(&(2.0 * X + 1.0), 3.0), None);
+    }
+
+    #[test]
+    fn abs_has_a_corner_at_zero_only() {
+        let f = X.abs();
+        assert!(!f.is_differentiable_at(0.0, 1e-6, 1e-3));
+        for x in [-2.0, -0.1, 0.1, 3.0] {
+            assert!(f.is_differentiable_at(x, 1e-6, 1e-3), "{x}");
+        }
+        assert!((f.numerical_derivative(-2.0, 1e-6) + 1.0).abs() < 1e-9);
+        assert_eq!(f.numerical_derivative(0.0, 1e-6), 0.0);
+    }
+
+    #[test]
+    fn prime_agrees_with_the_central_difference_for_every_variant() {
+        let inner = 0.5 * X + 0.25;
+        let cases = [
+            Function::from(3.0),
+            X,
+            X.exp() + X.sin(),
+            X.powf(3.0) - X.cos(),
+            X.sin() * X.ln(),
+            X.sin() / (X + 2.0),
+            X.powf(3.0),
+            X.powf(2.5),
+            X.powf(1.0 / 3.0),
+            Function::from(3.0).pow(&X),
+            (X + 1.0).pow(&X.sin()),
+            (X + 1.0).sqrt(),
+            inner.exp(),
+            (X + 0.5).ln(),
+            X.sin(),
+            X.cos(),
+            inner.tan(),
+            inner.asin(),
+            inner.acos(),
+            X.atan(),
+            X.sinh(),
+            X.cosh(),
+            X.tanh(),
+            (X - 0.5).abs(),
+            X.erf(),
+            X.sec() + X.csc() + X.cot(),
+        ];
+        for f in cases {
+            let derivative = f.prime();
+            for x in [0.35, 0.8, 1.3] {
+                let (symbolic, numeric) = (derivative.eval(x), f.numerical_derivative(x, 1e-6));
+                assert!((symbolic - numeric).abs() <= 1e-6 * symbolic.abs().max(1.0), "{f} at {x}: {symbolic} vs {numeric}");
+            }
+        }
+        assert_eq!(X.powf(2.0).symmetric_derivative(1.0, 0.5), X.powf(2.0).numerical_derivative(1.0, 0.5));
+    }
+}
@@ -1,289 +1,1048 @@
-#![allow(dead_code)]
 
-use std::{ops::{Add, Sub, Mul, Div, Deref}, rc::Rc};
+use alloc::{borrow::ToOwned, format, rc::Rc, string::{String, ToString}, vec, vec::Vec};
+use core::{hash::{Hash, Hasher}, mem::discriminant, ops::{Add, Sub, Mul, Div, Neg, Deref}};
+#[cfg(feature = "std")]
+use std::{collections::{HashMap, HashSet}, mem::Discriminant};
+
+use crate::math::{self, Float};
 
 #[derive(PartialEq, Clone, Debug)]
-pub struct FunctionRef {
-    f: Rc<Box<Function>>
+pub struct FunctionRef<T = f64> {
+    f: Rc<Function<T>>
 }
 
-impl FunctionRef {
-    fn as_fn(&self) -> &Function {
+impl<T> FunctionRef<T> {
+    fn as_fn(&self) -> &Function<T> {
         &self.f
     }
 
-    fn new(f: Function) -> Self {
+    pub(crate) fn new(f: Function<T>) -> Self {
         Self {
-            f: Rc::new(Box::new(f))
+            f: Rc::new(f)
         }
     }
 
-    fn clone_from(f: &Function) -> Self {
+    fn clone_from(f: &Function<T>) -> Self where T: Clone {
         Self::new(f.clone())
     }
 }
 
-impl Deref for FunctionRef {
-    type Target = Function;
+impl<T> Deref for FunctionRef<T> {
+    type Target = Function<T>;
 
     fn deref(&self) -> &Self::Target {
         self.as_fn()
     }
 }
 
-impl std::fmt::Display for FunctionRef {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+// serialize the pointed-to tree rather than the Rc, so a round trip rebuilds fresh nodes
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for FunctionRef<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_fn().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for FunctionRef<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Function::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl<T: Float> Hash for FunctionRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_fn().hash(state)
+    }
+}
+
+impl<T: Float> core::fmt::Display for FunctionRef<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         self.as_fn().fmt(f)
     }
 }
 
 #[derive(PartialEq, Clone, Debug)]
-pub enum Function {
-    Constant(f64),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Function<T = f64> {
+    Constant(T),
     X,
-    Add(FunctionRef, FunctionRef),
-    Subtract(FunctionRef, FunctionRef),
-    Multiply(FunctionRef, FunctionRef),
-    Divide(FunctionRef, FunctionRef),
-    Powi(FunctionRef, f64),
-    Powa(f64, FunctionRef),
-    Pow(FunctionRef, FunctionRef),
-    Exp(FunctionRef),
-    Ln(FunctionRef),
-    Sin(FunctionRef),
-    Cos(FunctionRef),
-    Tan(FunctionRef),
+    Add(FunctionRef<T>, FunctionRef<T>),
+    Subtract(FunctionRef<T>, FunctionRef<T>),
+    Multiply(FunctionRef<T>, FunctionRef<T>),
+    Divide(FunctionRef<T>, FunctionRef<T>),
+    Powi(FunctionRef<T>, T),
+    Powa(T, FunctionRef<T>),
+    Pow(FunctionRef<T>, FunctionRef<T>),
+    Sqrt(FunctionRef<T>),
+    Exp(FunctionRef<T>),
+    Ln(FunctionRef<T>),
+    Sin(FunctionRef<T>),
+    Cos(FunctionRef<T>),
+    Tan(FunctionRef<T>),
+    Asin(FunctionRef<T>),
+    Acos(FunctionRef<T>),
+    Atan(FunctionRef<T>),
+    Sinh(FunctionRef<T>),
+    Cosh(FunctionRef<T>),
+    Tanh(FunctionRef<T>),
+    Abs(FunctionRef<T>),
+    Erf(FunctionRef<T>),
 }
 
-impl Function {
-    pub fn eval(&self, x: f64) -> f64 {
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DiffRule {
+    ConstantRule,
+    IdentityRule,
+    SumRule,
+    DifferenceRule,
+    ConstantMultipleRule,
+    ProductRule,
+    QuotientRule,
+    PowerRule,
+    ExponentialRule,
+    LogarithmRule,
+    TrigRule,
+    ChainRule,
+    LogarithmicDifferentiation,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum EvalError {
+    DivisionByZero,
+    LogOfNonPositive,
+    NegativeBase,
+    OutOfDomain,
+}
+
+impl core::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::LogOfNonPositive => write!(f, "logarithm of a non-positive number"),
+            Self::NegativeBase => write!(f, "fractional power of a negative number"),
+            Self::OutOfDomain => write!(f, "inverse sine or cosine outside [-1, 1]"),
+        }
+    }
+}
+
+impl core::error::Error for EvalError {}
+
+impl<T: Float> Function<T> {
+    fn literal(a: f64) -> Self {
+        Self::Constant(T::of(a))
+    }
+
+    pub fn pi() -> Self {
+        Self::Constant(T::PI())
+    }
+
+    pub fn e() -> Self {
+        Self::Constant(T::E())
+    }
+
+    pub fn eval(&self, x: T) -> T {
+        self.eval_node(x, &mut |f| f.eval(x))
+    }
+
+    // the first error found bottom-up wins; a 0 to a negative power counts as a division by zero
+    pub fn try_eval(&self, x: T) -> Result<T, EvalError> {
+        let (mut operands, mut error) = (vec![], None);
+        let value = self.eval_node(x, &mut |f| match f.try_eval(x) {
+            Ok(value) => {
+                operands.push(value);
+                value
+            }
+            Err(e) => {
+                error.get_or_insert(e);
+                T::nan()
+            }
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+        let zero = T::zero();
+        match (self, operands.as_slice()) {
+            (Self::Divide(_, _), &[_, b]) if b == zero => Err(EvalError::DivisionByZero),
+            (Self::Ln(_), &[a]) if a <= zero => Err(EvalError::LogOfNonPositive),
+            (Self::Sqrt(_), &[a]) if a < zero => Err(EvalError::NegativeBase),
+            (Self::Powi(_, n), &[a]) if a == zero && *n < zero => Err(EvalError::DivisionByZero),
+            (Self::Powi(_, _), &[a]) if a < zero && value.is_nan() => Err(EvalError::NegativeBase),
+            (Self::Powa(a, _), &[b]) if *a == zero && b < zero => Err(EvalError::DivisionByZero),
+            (Self::Powa(a, _), &[b]) if *a < zero && b.fract() != zero => Err(EvalError::NegativeBase),
+            (Self::Pow(_, _), &[a, b]) if a == zero && b < zero => Err(EvalError::DivisionByZero),
+            (Self::Pow(_, _), &[a, b]) if a < zero && b.fract() != zero => Err(EvalError::NegativeBase),
+            (Self::Asin(_) | Self::Acos(_), &[a]) if a.abs() > T::one() => Err(EvalError::OutOfDomain),
+            _ => Ok(value),
+        }
+    }
+
+    pub fn eval_many(&self, xs: &[T]) -> Vec<T> {
+        xs.iter().map(|&x| self.eval(x)).collect()
+    }
+
+    pub fn eval_to_function(&self, x: T) -> Self {
+        Self::Constant(self.eval(x))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn eval_shared(&self, x: T) -> T {
+        self.eval_memoized(x, &mut HashMap::new())
+    }
+
+    #[cfg(feature = "std")]
+    fn eval_memoized(&self, x: T, memo: &mut HashMap<*const Function<T>, T>) -> T {
+        self.eval_node(x, &mut |f| {
+            if let Some(value) = memo.get(&Rc::as_ptr(&f.f)) {
+                return *value;
+            }
+            let value = f.eval_memoized(x, memo);
+            memo.insert(Rc::as_ptr(&f.f), value);
+            value
+        })
+    }
+
+    // only the final value is rounded; intermediate results keep full precision
+    pub fn eval_rounded(&self, x: T, decimals: u32) -> T {
+        let scale = T::of(10.0).powi(decimals as i32);
+        (self.eval(x) * scale).round() / scale
+    }
+
+    fn eval_node(&self, x: T, eval: &mut impl FnMut(&FunctionRef<T>) -> T) -> T {
+        #[cfg(test)]
+        tests::NODE_EVALUATIONS.with(|n| n.set(n.get() + 1));
         match self {
             Self::Constant(a) => *a,
             Self::X => x,
-            Self::Add(a, b) => a.eval(x) + b.eval(x),
-            Self::Subtract(a, b) => a.eval(x) - b.eval(x),
-            Self::Multiply(a, b) => a.eval(x) * b.eval(x),
-            Self::Divide(a, b) => a.eval(x) / b.eval(x),
-            Self::Powi(a, b) => a.eval(x).powf(*b),
-            Self::Powa(a, b) => a.powf(b.eval(x)),
-            Self::Pow(a, b) => a.eval(x).powf(b.eval(x)),
-            Self::Exp(a) => a.eval(x).exp(),
-            Self::Ln(a) => a.eval(x).ln(),
-            Self::Sin(a) => a.eval(x).sin(),
-            Self::Cos(a) => a.eval(x).cos(),
-            Self::Tan(a) => a.eval(x).tan(),
+            Self::Add(a, b) => eval(a) + eval(b),
+            Self::Subtract(a, b) => eval(a) - eval(b),
+            Self::Multiply(a, b) => eval(a) * eval(b),
+            Self::Divide(a, b) => eval(a) / eval(b),
+            Self::Powi(a, b) => real_pow(eval(a), *b),
+            Self::Powa(a, b) => math::powf(*a, eval(b)),
+            Self::Pow(a, b) => math::powf(eval(a), eval(b)),
+            Self::Sqrt(a) => math::sqrt(eval(a)),
+            Self::Exp(a) => math::exp(eval(a)),
+            Self::Ln(a) => math::ln(eval(a)),
+            Self::Sin(a) => math::sin(eval(a)),
+            Self::Cos(a) => math::cos(eval(a)),
+            Self::Tan(a) => math::tan(eval(a)),
+            Self::Asin(a) => math::asin(eval(a)),
+            Self::Acos(a) => math::acos(eval(a)),
+            Self::Atan(a) => math::atan(eval(a)),
+            Self::Sinh(a) => math::sinh(eval(a)),
+            Self::Cosh(a) => math::cosh(eval(a)),
+            Self::Tanh(a) => math::tanh(eval(a)),
+            Self::Abs(a) => eval(a).abs(),
+            Self::Erf(a) => math::erf(eval(a)),
         }
     }
 
     pub fn prime(&self) -> Self {
+        self.prime_node(&mut |f| f.prime())
+    }
+
+    fn prime_node(&self, prime: &mut impl FnMut(&FunctionRef<T>) -> Function<T>) -> Self {
         match self {
-            Self::Constant(_) => Self::Constant(0.0),
-            Self::X => Self::Constant(1.0),
-            Self::Add(f, g) => f.prime() + g.prime(),
-            Self::Subtract(f, g) => f.prime() - g.prime(),
+            Self::Constant(_) => Self::literal(0.0),
+            Self::X => Self::literal(1.0),
+            Self::Add(f, g) => prime(f) + prime(g),
+            Self::Subtract(f, g) => prime(f) - prime(g),
             Self::Multiply(f, g) => {
                 match (f.as_fn(), g.as_fn()) {
-                    (Function::Constant(_), Function::Constant(_)) => Function::Constant(0.0),
-                    (Function::Constant(a), f) => *a * f.prime(),
-                    (f, Function::Constant(a)) => *a * f.prime(),
-                    (f, g) => f.prime() * g + f * g.prime(),
+                    (Function::Constant(_), Function::Constant(_)) => Self::literal(0.0),
+                    (Function::Constant(a), _) => Self::scaled(*a, prime(g)),
+                    (_, Function::Constant(a)) => Self::scaled(*a, prime(f)),
+                    _ => prime(f) * g.as_fn() + f.as_fn() * prime(g),
                 }
             }
             Self::Divide(f, g) =>  {
                 match (f.as_fn(), g.as_fn()) {
-                    (Function::Constant(_), Function::Constant(_)) => Function::Constant(0.0),
-                    (Function::Constant(a), f) => *a / f.prime(),
-                    (f, Function::Constant(a)) => f.prime() / *a,
-                    (f, g) => (g * f.prime() - f * g.prime()) / g.powf(2.0),
+                    (Function::Constant(_), Function::Constant(_)) => Self::literal(0.0),
+                    // a * sec(h) and friends keep their textbook derivatives instead of the quotient rule's
+                    (Function::Constant(_), Function::Cos(h)) => self.clone() * h.tan() * prime(h),
+                    (Function::Constant(_), Function::Sin(h)) => -(self.clone() * h.cot()) * prime(h),
+                    (Function::Constant(a), Function::Tan(h)) => -(Self::Constant(*a) * h.csc().powf(T::of(2.0))) * prime(h),
+                    (Function::Constant(a), _) => Self::Constant(-*a) * prime(g) / g.powf(T::of(2.0)),
+                    (_, Function::Constant(a)) => prime(f) / Self::Constant(*a),
+                    _ => (g.as_fn() * prime(f) - f.as_fn() * prime(g)) / g.powf(T::of(2.0)),
                 }
             },
-            Self::Powi(f, a) => (*a * f.powf(a - 1.0)) * f.prime(),
-            Self::Powa(a, f) => Self::Powa(*a, f.clone()) * Self::Constant(a.ln()) * f.prime(),
-            Self::Pow(f, g) => f.pow(g) * g.prime() * f.ln() + g.as_fn() * f.prime() / f.as_fn(),
-            Self::Exp(f) => f.exp() * f.prime(),
-            Self::Ln(f) => f.prime() / f.as_fn(),
-            Self::Sin(f) => f.cos() * f.prime(),
-            Self::Cos(f) => -1.0 * f.sin() * f.prime(),
-            Self::Tan(f) => f.prime() / f.cos().powf(2.0),
+            Self::Powi(f, a) => (Self::Constant(*a) * f.powf(*a - T::one())) * prime(f),
+            Self::Powa(a, f) => Self::Powa(*a, f.clone()) * Self::Constant(math::ln(*a)) * prime(f),
+            Self::Pow(f, g) => f.pow(g) * (prime(g) * f.ln() + g.as_fn() * prime(f) / f.as_fn()),
+            Self::Sqrt(f) => prime(f) / (Self::literal(2.0) * f.sqrt()),
+            Self::Exp(f) => f.exp() * prime(f),
+            Self::Ln(f) => prime(f) / f.as_fn(),
+            Self::Sin(f) => f.cos() * prime(f),
+            Self::Cos(f) => -f.sin() * prime(f),
+            Self::Tan(f) => prime(f) / f.cos().powf(T::of(2.0)),
+            Self::Asin(f) => prime(f) / (Self::literal(1.0) - f.powf(T::of(2.0))).sqrt(),
+            Self::Acos(f) => -prime(f) / (Self::literal(1.0) - f.powf(T::of(2.0))).sqrt(),
+            Self::Atan(f) => prime(f) / (Self::literal(1.0) + f.powf(T::of(2.0))),
+            Self::Sinh(f) => f.cosh() * prime(f),
+            Self::Cosh(f) => f.sinh() * prime(f),
+            Self::Tanh(f) => prime(f) / f.cosh().powf(T::of(2.0)),
+            // f / |f| is the sign of f, which is 0 / 0 = NaN at the kink where f = 0
+            Self::Abs(f) => prime(f) * f.as_fn() / f.abs(),
+            Self::Erf(f) => Self::Constant(T::FRAC_2_SQRT_PI()) * (-f.powf(T::of(2.0))).exp() * prime(f),
+        }
+    }
+
+    // c * (b * g) becomes (c * b) * g, so repeated derivatives of x^n collect one integer coefficient
+    fn scaled(c: T, f: Self) -> Self {
+        match &f {
+            Self::Multiply(b, g) => match b.as_fn() {
+                Self::Constant(b) => Self::Constant(c * *b) * g.as_fn(),
+                _ => Self::Constant(c) * f,
+            },
+            _ => Self::Constant(c) * f,
+        }
+    }
+
+    pub fn prime_rule(&self) -> DiffRule {
+        let inner = match self {
+            Self::Constant(_) => return DiffRule::ConstantRule,
+            Self::X => return DiffRule::IdentityRule,
+            Self::Add(_, _) => return DiffRule::SumRule,
+            Self::Subtract(_, _) => return DiffRule::DifferenceRule,
+            Self::Multiply(f, g) => return match (f.as_fn(), g.as_fn()) {
+                (Function::Constant(_), _) | (_, Function::Constant(_)) => DiffRule::ConstantMultipleRule,
+                _ => DiffRule::ProductRule,
+            },
+            Self::Divide(_, g) => return match g.as_fn() {
+                Function::Constant(_) => DiffRule::ConstantMultipleRule,
+                _ => DiffRule::QuotientRule,
+            },
+            Self::Pow(_, _) => return DiffRule::LogarithmicDifferentiation,
+            Self::Powi(f, _) | Self::Powa(_, f) | Self::Sqrt(f) | Self::Exp(f) | Self::Ln(f)
+                | Self::Sin(f) | Self::Cos(f) | Self::Tan(f) | Self::Asin(f) | Self::Acos(f) | Self::Atan(f)
+                | Self::Sinh(f) | Self::Cosh(f) | Self::Tanh(f) | Self::Abs(f) | Self::Erf(f) => f,
+        };
+        if inner.as_fn() != &Function::X {
+            return DiffRule::ChainRule;
+        }
+        match self {
+            Self::Powi(_, _) | Self::Sqrt(_) => DiffRule::PowerRule,
+            Self::Powa(_, _) | Self::Exp(_) => DiffRule::ExponentialRule,
+            Self::Ln(_) => DiffRule::LogarithmRule,
+            _ => DiffRule::TrigRule,
+        }
+    }
+
+    pub fn nth_derivative(&self, n: u64) -> Self {
+        let mut derivative = self.clone();
+        for _ in 0..n {
+            // every later derivative of zero is zero, so a huge n doesn't keep looping
+            if derivative == Self::literal(0.0) {
+                break;
+            }
+            derivative = derivative.prime();
+        }
+        derivative
+    }
+
+    #[cfg(feature = "std")]
+    pub fn nth_derivative_shared(&self, n: u64) -> Self {
+        let mut cache = DerivativeCache::default();
+        let mut derivative = self.clone();
+        for _ in 0..n {
+            if derivative == Self::literal(0.0) {
+                break;
+            }
+            derivative = cache.prime(&derivative);
+        }
+        derivative
+    }
+
+    // subtrees shared through the same Rc are only counted once
+    #[cfg(feature = "std")]
+    pub fn node_count(&self) -> usize {
+        self.count_distinct(&mut HashSet::new())
+    }
+
+    #[cfg(feature = "std")]
+    fn count_distinct(&self, seen: &mut HashSet<*const Function<T>>) -> usize {
+        let mut count = 1;
+        self.with_children(&mut |child| {
+            if seen.insert(Rc::as_ptr(&child.f)) {
+                count += child.count_distinct(seen);
+            }
+            child.clone()
+        });
+        count
+    }
+
+    pub(crate) fn map_children(&self, op: impl Fn(&Function<T>) -> Function<T>) -> Self {
+        match self {
+            Self::Constant(_) | Self::X => self.clone(),
+            Self::Add(a, b) => op(a) + op(b),
+            Self::Subtract(a, b) => op(a) - op(b),
+            Self::Multiply(a, b) => op(a) * op(b),
+            Self::Divide(a, b) => op(a) / op(b),
+            Self::Powi(a, b) => op(a).powf(*b),
+            Self::Powa(a, b) => Self::Constant(*a).pow(&op(b)),
+            Self::Pow(a, b) => op(a).pow(&op(b)),
+            Self::Sqrt(a) => op(a).sqrt(),
+            Self::Exp(a) => op(a).exp(),
+            Self::Ln(a) => op(a).ln(),
+            Self::Sin(a) => op(a).sin(),
+            Self::Cos(a) => op(a).cos(),
+            Self::Tan(a) => op(a).tan(),
+            Self::Asin(a) => op(a).asin(),
+            Self::Acos(a) => op(a).acos(),
+            Self::Atan(a) => op(a).atan(),
+            Self::Sinh(a) => op(a).sinh(),
+            Self::Cosh(a) => op(a).cosh(),
+            Self::Tanh(a) => op(a).tanh(),
+            Self::Abs(a) => op(a).abs(),
+            Self::Erf(a) => op(a).erf(),
+        }
+    }
+
+    // unlike map_children this rebuilds the node as it is, without any of the constructors' folding
+    fn with_children(&self, op: &mut impl FnMut(&FunctionRef<T>) -> FunctionRef<T>) -> Self {
+        match self {
+            Self::Constant(_) | Self::X => self.clone(),
+            Self::Add(a, b) => Self::Add(op(a), op(b)),
+            Self::Subtract(a, b) => Self::Subtract(op(a), op(b)),
+            Self::Multiply(a, b) => Self::Multiply(op(a), op(b)),
+            Self::Divide(a, b) => Self::Divide(op(a), op(b)),
+            Self::Powi(a, b) => Self::Powi(op(a), *b),
+            Self::Powa(a, b) => Self::Powa(*a, op(b)),
+            Self::Pow(a, b) => Self::Pow(op(a), op(b)),
+            Self::Sqrt(a) => Self::Sqrt(op(a)),
+            Self::Exp(a) => Self::Exp(op(a)),
+            Self::Ln(a) => Self::Ln(op(a)),
+            Self::Sin(a) => Self::Sin(op(a)),
+            Self::Cos(a) => Self::Cos(op(a)),
+            Self::Tan(a) => Self::Tan(op(a)),
+            Self::Asin(a) => Self::Asin(op(a)),
+            Self::Acos(a) => Self::Acos(op(a)),
+            Self::Atan(a) => Self::Atan(op(a)),
+            Self::Sinh(a) => Self::Sinh(op(a)),
+            Self::Cosh(a) => Self::Cosh(op(a)),
+            Self::Tanh(a) => Self::Tanh(op(a)),
+            Self::Abs(a) => Self::Abs(op(a)),
+            Self::Erf(a) => Self::Erf(op(a)),
+        }
+    }
+
+    pub fn compose(&self, inner: &Function<T>) -> Self {
+        match self {
+            Self::X => inner.clone(),
+            _ => self.map_children(|f| f.compose(inner)),
+        }
+    }
+
+    pub fn elasticity(&self) -> Self {
+        Function::X * self.prime() / self
+    }
+
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Add(_, _) | Self::Subtract(_, _) => 1,
+            Self::Multiply(_, _) | Self::Divide(_, _) => 2,
+            Self::Powi(_, _) | Self::Powa(_, _) | Self::Pow(_, _) | Self::Exp(_) => 3,
+            Self::Constant(_) | Self::X | Self::Sqrt(_) | Self::Ln(_) | Self::Sin(_) | Self::Cos(_) | Self::Tan(_)
+                | Self::Asin(_) | Self::Acos(_) | Self::Atan(_)
+                | Self::Sinh(_) | Self::Cosh(_) | Self::Tanh(_) | Self::Abs(_) | Self::Erf(_) => 4,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Constant(_) | Self::X => 0,
+            Self::Powi(_, _) | Self::Powa(_, _) | Self::Sqrt(_) | Self::Exp(_) | Self::Ln(_) | Self::Sin(_) | Self::Cos(_) | Self::Tan(_)
+                | Self::Asin(_) | Self::Acos(_) | Self::Atan(_)
+                | Self::Sinh(_) | Self::Cosh(_) | Self::Tanh(_) | Self::Abs(_) | Self::Erf(_) => 1,
+            Self::Add(_, _) | Self::Subtract(_, _) | Self::Multiply(_, _) | Self::Divide(_, _) | Self::Pow(_, _) => 2,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::Constant(_) | Self::X => 1,
+            Self::Add(a, b) | Self::Subtract(a, b) | Self::Multiply(a, b) | Self::Divide(a, b) | Self::Pow(a, b) => {
+                1 + a.depth().max(b.depth())
+            }
+            Self::Powi(a, _) | Self::Powa(_, a) => 1 + a.depth(),
+            Self::Sqrt(a) | Self::Exp(a) | Self::Ln(a) | Self::Sin(a) | Self::Cos(a) | Self::Tan(a)
+                | Self::Asin(a) | Self::Acos(a) | Self::Atan(a)
+                | Self::Sinh(a) | Self::Cosh(a) | Self::Tanh(a) | Self::Abs(a) | Self::Erf(a) => 1 + a.depth(),
+        }
+    }
+
+    pub fn rebalance(&self) -> Self {
+        match self {
+            Self::Add(_, _) | Self::Multiply(_, _) => {
+                let mut operands = vec![];
+                self.flatten_into(self, &mut operands);
+                let operands: Vec<Function<T>> = operands.iter().map(|f| f.rebalance()).collect();
+                Self::balanced(self, &operands)
+            }
+            Self::Constant(_) | Self::X => self.clone(),
+            Self::Subtract(a, b) => Self::Subtract(FunctionRef::new(a.rebalance()), FunctionRef::new(b.rebalance())),
+            Self::Divide(a, b) => Self::Divide(FunctionRef::new(a.rebalance()), FunctionRef::new(b.rebalance())),
+            Self::Pow(a, b) => Self::Pow(FunctionRef::new(a.rebalance()), FunctionRef::new(b.rebalance())),
+            Self::Powi(a, b) => Self::Powi(FunctionRef::new(a.rebalance()), *b),
+            Self::Powa(a, b) => Self::Powa(*a, FunctionRef::new(b.rebalance())),
+            Self::Sqrt(a) => Self::Sqrt(FunctionRef::new(a.rebalance())),
+            Self::Exp(a) => Self::Exp(FunctionRef::new(a.rebalance())),
+            Self::Ln(a) => Self::Ln(FunctionRef::new(a.rebalance())),
+            Self::Sin(a) => Self::Sin(FunctionRef::new(a.rebalance())),
+            Self::Cos(a) => Self::Cos(FunctionRef::new(a.rebalance())),
+            Self::Tan(a) => Self::Tan(FunctionRef::new(a.rebalance())),
+            Self::Asin(a) => Self::Asin(FunctionRef::new(a.rebalance())),
+            Self::Acos(a) => Self::Acos(FunctionRef::new(a.rebalance())),
+            Self::Atan(a) => Self::Atan(FunctionRef::new(a.rebalance())),
+            Self::Sinh(a) => Self::Sinh(FunctionRef::new(a.rebalance())),
+            Self::Cosh(a) => Self::Cosh(FunctionRef::new(a.rebalance())),
+            Self::Tanh(a) => Self::Tanh(FunctionRef::new(a.rebalance())),
+            Self::Abs(a) => Self::Abs(FunctionRef::new(a.rebalance())),
+            Self::Erf(a) => Self::Erf(FunctionRef::new(a.rebalance())),
+        }
+    }
+
+    // + and * chains are flattened and sorted by their printed form, so operand order doesn't affect == or the hash
+    pub fn canonicalize(&self) -> Self {
+        match self {
+            Self::Add(_, _) | Self::Multiply(_, _) => {
+                let mut operands = vec![];
+                self.flatten_into(self, &mut operands);
+                let mut operands: Vec<Function<T>> = operands.iter().map(|f| f.canonicalize()).collect();
+                operands.sort_by_cached_key(Function::structural_key);
+                Self::balanced(self, &operands)
+            }
+            _ => self.with_children(&mut |f| FunctionRef::new(f.canonicalize())),
+        }
+    }
+
+    // Every node's kind and constant, in preorder. Each kind has a fixed arity, so the key spells out the
+    // whole tree: equal keys mean equal trees, which sorting by the Display string didn't guarantee.
+    fn structural_key(&self) -> Vec<(u8, (u64, i16, i8))> {
+        let mut key = vec![];
+        self.push_structural_key(&mut key);
+        key
+    }
+
+    fn push_structural_key(&self, key: &mut Vec<(u8, (u64, i16, i8))>) {
+        let float = match self {
+            // adding 0 turns -0 into 0, as in Hash
+            Self::Constant(a) | Self::Powi(_, a) | Self::Powa(a, _) => (*a + T::zero()).integer_decode(),
+            _ => (0, 0, 0),
+        };
+        key.push((self.kind(), float));
+        self.with_children(&mut |child| {
+            child.push_structural_key(key);
+            child.clone()
+        });
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Constant(_) => 0,
+            Self::X => 1,
+            Self::Add(_, _) => 2,
+            Self::Subtract(_, _) => 3,
+            Self::Multiply(_, _) => 4,
+            Self::Divide(_, _) => 5,
+            Self::Powi(_, _) => 6,
+            Self::Powa(_, _) => 7,
+            Self::Pow(_, _) => 8,
+            Self::Sqrt(_) => 9,
+            Self::Exp(_) => 10,
+            Self::Ln(_) => 11,
+            Self::Sin(_) => 12,
+            Self::Cos(_) => 13,
+            Self::Tan(_) => 14,
+            Self::Asin(_) => 15,
+            Self::Acos(_) => 16,
+            Self::Atan(_) => 17,
+            Self::Sinh(_) => 18,
+            Self::Cosh(_) => 19,
+            Self::Tanh(_) => 20,
+            Self::Abs(_) => 21,
+            Self::Erf(_) => 22,
+        }
+    }
+
+    pub(crate) fn flatten_into<'a>(&'a self, chain: &Function<T>, operands: &mut Vec<&'a Function<T>>) {
+        match (self, chain) {
+            (Self::Add(a, b), Self::Add(_, _)) | (Self::Multiply(a, b), Self::Multiply(_, _)) => {
+                a.flatten_into(chain, operands);
+                b.flatten_into(chain, operands);
+            }
+            _ => operands.push(self),
+        }
+    }
+
+    fn balanced(chain: &Function<T>, operands: &[Function<T>]) -> Self {
+        if operands.len() == 1 {
+            return operands[0].clone();
+        }
+        let (left, right) = operands.split_at(operands.len() / 2);
+        let (left, right) = (FunctionRef::new(Self::balanced(chain, left)), FunctionRef::new(Self::balanced(chain, right)));
+        match chain {
+            Self::Multiply(_, _) => Self::Multiply(left, right),
+            _ => Self::Add(left, right),
         }
     }
 
     pub fn pow(&self, other: &Self) -> Self {
-        if self == &Function::Constant(0.0) {
-            return Self::Constant(0.0);
+        if self == &Self::literal(0.0) {
+            return Self::literal(0.0);
         } 
-        if self == &Function::Constant(1.0) {
-            return Self::Constant(1.0);
+        if self == &Self::literal(1.0) {
+            return Self::literal(1.0);
         } 
-        if other == &Function::Constant(0.0) {
-            return Self::Constant(1.0);
+        if other == &Self::literal(0.0) {
+            return Self::literal(1.0);
         } 
-        if other == &Function::Constant(1.0) {
+        if other == &Self::literal(1.0) {
             return self.clone();
         } 
         match (self, other) {
-            (Function::Constant(a), Function::Constant(b)) => Self::Constant(a.powf(*b)),
+            (f, Function::Constant(a)) if *a == T::of(0.5) => f.sqrt(),
+            (Function::Constant(a), Function::Constant(b)) => Self::Constant(real_pow(*a, *b)),
             (f, Function::Constant(a)) => Self::Powi(FunctionRef::clone_from(f), *a),
+            (Function::Constant(a), f) if *a == T::E() => f.exp(),
             (Function::Constant(a), f) => Self::Powa(*a, FunctionRef::clone_from(f)),
             (f, g) => Self::Pow(FunctionRef::clone_from(f), FunctionRef::clone_from(g)),
         }
     }
 
-    pub fn powf(&self, other: f64) -> Self {
+    pub fn powf(&self, other: T) -> Self {
         self.pow(&Function::Constant(other))
     }
 
+    pub fn sqrt(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::sqrt(*a)),
+            _ => Self::Sqrt(FunctionRef::clone_from(self))
+        }
+    }
+
     pub fn exp(&self) -> Self {
         match self {
-            Self::Constant(a) => Function::Constant(a.exp()),
+            Self::Constant(a) => Function::Constant(math::exp(*a)),
             _ => Self::Exp(FunctionRef::clone_from(self))
         }
     }
 
     pub fn ln(&self) -> Self {
         match self {
-            Self::Constant(a) => Function::Constant(a.ln()),
+            Self::Constant(a) => Function::Constant(math::ln(*a)),
             _ => Self::Ln(FunctionRef::clone_from(self))
         }
     }
 
     pub fn sin(&self) -> Self {
         match self {
-            Self::Constant(a) => Function::Constant(a.sin()),
+            Self::Constant(a) => Function::Constant(math::sin(*a)),
             _ => Self::Sin(FunctionRef::clone_from(self))
         }
     }
 
     pub fn cos(&self) -> Self {
         match self {
-            Self::Constant(a) => Function::Constant(a.cos()),
+            Self::Constant(a) => Function::Constant(math::cos(*a)),
             _ => Self::Cos(FunctionRef::clone_from(self))
         }
     }
 
     pub fn tan(&self) -> Self {
         match self {
-            Self::Constant(a) => Function::Constant(a.tan()),
+            Self::Constant(a) => Function::Constant(math::tan(*a)),
             _ => Self::Tan(FunctionRef::clone_from(self))
         }
     }
+
+    // the reciprocals are built from cos, sin and tan rather than being variants of their own
+    pub fn sec(&self) -> Self {
+        Self::literal(1.0) / self.cos()
+    }
+
+    pub fn csc(&self) -> Self {
+        Self::literal(1.0) / self.sin()
+    }
+
+    pub fn cot(&self) -> Self {
+        Self::literal(1.0) / self.tan()
+    }
+
+    // the name and argument for the 1 / cos(f) shapes sec, csc and cot build
+    fn reciprocal_trig(&self) -> Option<(&'static str, &FunctionRef<T>)> {
+        let Self::Divide(a, b) = self else { return None };
+        if **a != Self::literal(1.0) {
+            return None;
+        }
+        match b.as_fn() {
+            Self::Cos(f) => Some(("sec", f)),
+            Self::Sin(f) => Some(("csc", f)),
+            Self::Tan(f) => Some(("cot", f)),
+            _ => None,
+        }
+    }
+
+    pub fn asin(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::asin(*a)),
+            _ => Self::Asin(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn acos(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::acos(*a)),
+            _ => Self::Acos(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn atan(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::atan(*a)),
+            _ => Self::Atan(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn sinh(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::sinh(*a)),
+            _ => Self::Sinh(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn cosh(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::cosh(*a)),
+            _ => Self::Cosh(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn tanh(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::tanh(*a)),
+            _ => Self::Tanh(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(a.abs()),
+            Self::Abs(_) => self.clone(),
+            _ => Self::Abs(FunctionRef::clone_from(self))
+        }
+    }
+
+    pub fn erf(&self) -> Self {
+        match self {
+            Self::Constant(a) => Function::Constant(math::erf(*a)),
+            _ => Self::Erf(FunctionRef::clone_from(self))
+        }
+    }
+}
+
+// simplify only knows f64 constants
+#[cfg(feature = "std")]
+impl Function {
+    pub fn derivative(&self) -> Self {
+        self.prime().simplify()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn schwarzian(&self) -> Self {
+        let first = self.prime();
+        let second = first.prime();
+        let third = second.prime();
+        (&third / &first - 1.5 * (second / &first).powf(2.0)).simplify()
+    }
+}
+
+#[cfg(feature = "std")]
+type NodeKey<T> = (Discriminant<Function<T>>, Vec<*const Function<T>>, Vec<(u64, i16, i8)>);
+
+// Equal subtrees are interned to a single Rc and each one is differentiated once, so repeated
+// derivatives grow a shared graph instead of copying the product rule's subtrees over and over.
+#[cfg(feature = "std")]
+pub struct DerivativeCache<T = f64> {
+    interned: HashMap<NodeKey<T>, FunctionRef<T>>,
+    // the original Ref is kept so its address can't be reused by another node while it is a key
+    canonical: HashMap<*const Function<T>, (FunctionRef<T>, FunctionRef<T>)>,
+    primes: HashMap<*const Function<T>, FunctionRef<T>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> Default for DerivativeCache<T> {
+    fn default() -> Self {
+        Self { interned: HashMap::new(), canonical: HashMap::new(), primes: HashMap::new() }
+    }
 }
 
-impl core::fmt::Display for Function {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+#[cfg(feature = "std")]
+impl<T: Float> DerivativeCache<T> {
+    pub fn prime(&mut self, f: &Function<T>) -> Function<T> {
+        let f = self.intern(f);
+        self.prime_ref(&f).as_fn().clone()
+    }
+
+    fn prime_ref(&mut self, f: &FunctionRef<T>) -> FunctionRef<T> {
+        let f = self.canonical(f);
+        if let Some(prime) = self.primes.get(&Rc::as_ptr(&f.f)) {
+            return prime.clone();
+        }
+        let prime = f.prime_node(&mut |g| self.prime_ref(g).as_fn().clone());
+        let prime = self.intern(&prime);
+        self.primes.insert(Rc::as_ptr(&f.f), prime.clone());
+        prime
+    }
+
+    fn canonical(&mut self, f: &FunctionRef<T>) -> FunctionRef<T> {
+        if let Some((_, canonical)) = self.canonical.get(&Rc::as_ptr(&f.f)) {
+            return canonical.clone();
+        }
+        let canonical = self.intern(f);
+        self.canonical.insert(Rc::as_ptr(&f.f), (f.clone(), canonical.clone()));
+        canonical
+    }
+
+    fn intern(&mut self, f: &Function<T>) -> FunctionRef<T> {
+        let mut children = vec![];
+        let node = f.with_children(&mut |child| {
+            let child = self.canonical(child);
+            children.push(Rc::as_ptr(&child.f));
+            child
+        });
+        let floats = match node {
+            Function::Constant(a) | Function::Powi(_, a) | Function::Powa(a, _) => vec![a.integer_decode()],
+            _ => vec![],
+        };
+        let interned = self.interned.entry((discriminant(&node), children, floats))
+            .or_insert_with(|| FunctionRef::new(node))
+            .clone();
+        self.canonical.insert(Rc::as_ptr(&interned.f), (interned.clone(), interned.clone()));
+        interned
+    }
+}
+
+// odd roots keep the sign of negative bases, which powf alone would turn into NaN
+// and integer exponents use powi, about twice as fast as powf but a few ulps off for larger powers
+pub(crate) fn real_pow<T: Float>(base: T, exponent: T) -> T {
+    if exponent == T::one() / T::of(3.0) {
+        math::cbrt(base)
+    } else if let Some(n) = exponent.to_i32().filter(|_| exponent.fract() == T::zero()) {
+        base.powi(n)
+    } else {
+        math::powf(base, exponent)
+    }
+}
+
+// The right side of - and / needs strictly higher precedence, while + and * are associative so equal is
+// enough; ^ is right-associative, so it's the base that needs more. A negative constant as a base is
+// wrapped since -2 ^ 2 reads as -(2 ^ 2).
+fn operand<T: Float>(f: &Function<T>, min_precedence: u8) -> String {
+    match f {
+        Function::Constant(a) if *a < T::zero() && min_precedence > 3 => format!("({})", f),
+        _ if f.precedence() < min_precedence && f.reciprocal_trig().is_none() => format!("({})", f),
+        _ => f.to_string(),
+    }
+}
+
+impl<T: Float> core::fmt::Display for Function<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Some((name, a)) = self.reciprocal_trig() {
+            return write!(f, "{}({})", name, a);
+        }
         let repr = match self {
+            Self::Constant(a) if *a == T::PI() => "π".to_owned(),
+            Self::Constant(a) if *a == T::E() => "e".to_owned(),
             Self::Constant(a) => format!("{}", a),
             Self::X => "x".to_owned(),
-            Self::Add(a, b) => format!("({} + {})", a, b),
-            Self::Subtract(a, b) => format!("({} - {})", a, b),
-            Self::Multiply(a, b) => format!("({} * {})", a, b),
-            Self::Divide(a, b) => format!("({} / {})", a, b),
-            Self::Powi(a, b) => format!("({} ^ {})", a, b),
-            Self::Powa(a, b) => format!("({} ^ {})", a, b),
-            Self::Pow(a, b) => format!("({} ^ {})", a, b),
-            Self::Exp(a) => format!("(e ^ {})", a),
+            Self::Add(a, b) => format!("{} + {}", operand(a, 1), operand(b, 1)),
+            Self::Subtract(a, b) => format!("{} - {}", operand(a, 1), operand(b, 2)),
+            Self::Multiply(a, b) if **a == Self::literal(-1.0) => format!("-{}", operand(b, 3)),
+            Self::Multiply(a, b) => format!("{} * {}", operand(a, 2), operand(b, 2)),
+            Self::Divide(a, b) => format!("{} / {}", operand(a, 2), operand(b, 3)),
+            Self::Powi(a, b) if *b == T::one() / T::of(3.0) => format!("cbrt({})", a),
+            Self::Powi(a, b) => format!("{} ^ {}", operand(a, 4), b),
+            Self::Powa(a, b) => format!("{} ^ {}", operand(&Self::Constant(*a), 4), operand(b, 3)),
+            Self::Pow(a, b) => format!("{} ^ {}", operand(a, 4), operand(b, 3)),
+            Self::Sqrt(a) => format!("sqrt({})", a),
+            Self::Exp(a) => format!("e ^ {}", operand(a, 3)),
             Self::Ln(a) => format!("ln({})", a),
             Self::Sin(a) => format!("sin({})", a),
             Self::Cos(a) => format!("cos({})", a),
             Self::Tan(a) => format!("tan({})", a),
+            Self::Asin(a) => format!("asin({})", a),
+            Self::Acos(a) => format!("acos({})", a),
+            Self::Atan(a) => format!("atan({})", a),
+            Self::Sinh(a) => format!("sinh({})", a),
+            Self::Cosh(a) => format!("cosh({})", a),
+            Self::Tanh(a) => format!("tanh({})", a),
+            Self::Abs(a) => format!("|{}|", a),
+            Self::Erf(a) => format!("erf({})", a),
         };
         write!(f, "{}", repr)
     }
 }
 
-impl Add for Function {
-    type Output = Function;
+// the same structure PartialEq compares, with -0.0 hashed as 0.0 since the two are ==
+impl<T: Float> Hash for Function<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let float = |a: T| (a + T::zero()).integer_decode();
+        discriminant(self).hash(state);
+        match self {
+            Self::Constant(a) => float(*a).hash(state),
+            Self::X => {}
+            Self::Add(a, b) | Self::Subtract(a, b) | Self::Multiply(a, b) | Self::Divide(a, b) | Self::Pow(a, b) => {
+                a.hash(state);
+                b.hash(state);
+            }
+            Self::Powi(a, n) => {
+                a.hash(state);
+                float(*n).hash(state);
+            }
+            Self::Powa(a, b) => {
+                float(*a).hash(state);
+                b.hash(state);
+            }
+            Self::Sqrt(a) | Self::Exp(a) | Self::Ln(a) | Self::Sin(a) | Self::Cos(a) | Self::Tan(a)
+                | Self::Asin(a) | Self::Acos(a) | Self::Atan(a)
+                | Self::Sinh(a) | Self::Cosh(a) | Self::Tanh(a) | Self::Abs(a) | Self::Erf(a) => a.hash(state),
+        }
+    }
+}
+
+impl<T: Float> Add for Function<T> {
+    type Output = Function<T>;
 
     fn add(self, other: Self) -> Self::Output {
-        if self == Function::Constant(0.0) {
+        if self == Function::literal(0.0) {
             return other;
         }
-        if other == Function::Constant(0.0) {
+        if other == Function::literal(0.0) {
             return self;
         }
         match (self, other) {
-            (Function::Constant(a), Function::Constant(b)) => Function::Constant(f64::add(a, b)),
+            (Function::Constant(a), Function::Constant(b)) => Function::Constant(a + b),
             (f, g) => Function::Add(FunctionRef::new(f), FunctionRef::new(g)),
         }
     }
 }
 
-impl Sub for Function {
-    type Output = Function;
+impl<T: Float> Sub for Function<T> {
+    type Output = Function<T>;
 
     fn sub(self, other: Self) -> Self::Output {
-        if self == Function::Constant(0.0) {
-            return other;
-        }
-        if other == Function::Constant(0.0) {
+        if other == Function::literal(0.0) {
             return self;
         }
+        if self == Function::literal(0.0) {
+            return -other;
+        }
         match (self, other) {
-            (Function::Constant(a), Function::Constant(b)) => Function::Constant(f64::sub(a, b)),
+            (Function::Constant(a), Function::Constant(b)) => Function::Constant(a - b),
             (f, g) => Function::Subtract(FunctionRef::new(f), FunctionRef::new(g)),
         }
     }
 }
 
-impl Mul for Function {
-    type Output = Function;
+impl<T: Float> Mul for Function<T> {
+    type Output = Function<T>;
 
     fn mul(self, other: Self) -> Self::Output {
-        if self == Function::Constant(0.0) || other == Function::Constant(0.0) {
-            return Function::Constant(0.0);
+        if self == Function::literal(0.0) || other == Function::literal(0.0) {
+            return Function::literal(0.0);
         }
-        if self == Function::Constant(1.0) {
+        if self == Function::literal(1.0) {
             return other;
         }
-        if other == Function::Constant(1.0) {
+        if other == Function::literal(1.0) {
             return self;
         }
         match (self, other) {
-            (Function::Constant(a), Function::Constant(b)) => Function::Constant(f64::mul(a, b)),
+            (Function::Constant(a), Function::Constant(b)) => Function::Constant(a * b),
             (f, g) => Function::Multiply(FunctionRef::new(f), FunctionRef::new(g)),
         }
     }
 }
 
-impl Div for Function {
-    type Output = Function;
+impl<T: Float> Div for Function<T> {
+    type Output = Function<T>;
 
     fn div(self, other: Self) -> Self::Output {
-        if self == Function::Constant(0.0) {
-            return Function::Constant(0.0);
+        if self == Function::literal(0.0) {
+            return Function::literal(0.0);
         }
         match (self, other) {
-            (Function::Constant(a), Function::Constant(b)) => Function::Constant(f64::div(a, b)),
+            (Function::Constant(a), Function::Constant(b)) => Function::Constant(a / b),
             (f, g) => Function::Divide(FunctionRef::new(f), FunctionRef::new(g)),
         }
     }
 }
 
+// negation is a multiply by -1, which Display writes back as a leading -
+impl<T: Float> Neg for Function<T> {
+    type Output = Function<T>;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Function::Constant(a) => Function::Constant(-a),
+            Function::Multiply(a, b) if *a == Function::literal(-1.0) => b.as_fn().clone(),
+            f => Function::literal(-1.0) * f,
+        }
+    }
+}
+
+impl<T: Float> Neg for &Function<T> {
+    type Output = Function<T>;
+
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
 macro_rules! function_binop {
     (impl $imp:ident, $method:ident as $variant:ident) => {
-        impl $imp for &Function {
-            type Output = Function;
+        impl<T: Float> $imp for &Function<T> {
+            type Output = Function<T>;
 
             fn $method(self, other: Self) -> Self::Output {
                 Function::$method(self.clone(), other.clone())
             }
         }
 
-        impl $imp<Function> for &Function {
-            type Output = Function;
+        impl<T: Float> $imp<Function<T>> for &Function<T> {
+            type Output = Function<T>;
 
-            fn $method(self, other: Function) -> Self::Output {
+            fn $method(self, other: Function<T>) -> Self::Output {
                 Function::$method(self.clone(), other)
             }
         }
 
-        impl $imp<&Function> for Function {
-            type Output = Function;
+        impl<T: Float> $imp<&Function<T>> for Function<T> {
+            type Output = Function<T>;
 
-            fn $method(self, other: &Function) -> Self::Output {
+            fn $method(self, other: &Function<T>) -> Self::Output {
                 Function::$method(self, other.clone())
             }
         }
+
+        impl<T: Float> $imp<T> for Function<T> {
+            type Output = Function<T>;
+
+            fn $method(self, other: T) -> Self::Output {
+                Function::$method(self, Function::Constant(other))
+            }
+        }
+
+        impl<T: Float> $imp<T> for &Function<T> {
+            type Output = Function<T>;
+
+            fn $method(self, other: T) -> Self::Output {
+                Function::$method(self.clone(), Function::Constant(other))
+            }
+        }
     };
 }
 
@@ -292,45 +1051,30 @@ function_binop!(impl Sub, sub as Subtract);
 function_binop!(impl Mul, mul as Multiply);
 function_binop!(impl Div, div as Divide);
 
-impl From<f64> for Function {
-    fn from(val: f64) -> Self {
+impl<T: Float> From<T> for Function<T> {
+    fn from(val: T) -> Self {
         Self::Constant(val)
     }
 }
 
+// a scalar on the left is only f64, since an f32 impl as well would leave 2.0 * f ambiguous for unannotated f
 macro_rules! float_binop {
     (impl $imp:ident, $method:ident) => {
-        impl $imp<&Function> for f64 {
-            type Output = Function;
+        impl $imp<&Function<f64>> for f64 {
+            type Output = Function<f64>;
 
-            fn $method(self, other: &Function) -> Self::Output {
+            fn $method(self, other: &Function<f64>) -> Self::Output {
                 Function::$method(Function::Constant(self), other)
             }
         }
 
-        impl $imp<Function> for f64 {
-            type Output = Function;
+        impl $imp<Function<f64>> for f64 {
+            type Output = Function<f64>;
 
-            fn $method(self, other: Function) -> Self::Output {
+            fn $method(self, other: Function<f64>) -> Self::Output {
                 Function::$method(Function::Constant(self), other)
             }
         }
-
-        impl $imp<f64> for Function {
-            type Output = Function;
-
-            fn $method(self, other: f64) -> Self::Output {
-                Function::$method(self, Function::Constant(other))
-            }
-        }
-
-        impl $imp<f64> for &Function {
-            type Output = Function;
-
-            fn $method(self, other: f64) -> Self::Output {
-                Function::$method(self.clone(), Function::Constant(other))
-            }
-        }
     };
 }
 
@@ -338,3 +1082,330 @@ float_binop!(impl Add, add);
 float_binop!(impl Sub, sub);
 float_binop!(impl Mul, mul);
 float_binop!(impl Div, div);
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use std::cell::Cell;
+
+    use super::*;
+
+    const X: Function = Function::X;
+
+    // bumped by every eval_node call, so tests can count how many nodes an evaluation visits
+    std::thread_local! {
+        pub(super) static NODE_EVALUATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[cfg(feature = "std")]
+    fn evaluations(eval: impl FnOnce() -> f64) -> (f64, usize) {
+        NODE_EVALUATIONS.with(|n| n.set(0));
+        let value = eval();
+        (value, NODE_EVALUATIONS.with(Cell::get))
+    }
+
+    #[test]
+    fn fractional_powers_display_as_roots() {
+        assert_eq!(X.powf(0.5).to_string(), "sqrt(x)");
+        assert_eq!(X.powf(1.0 / 3.0).to_string(), "cbrt(x)");
+    }
+
+    #[test]
+    fn cube_root_keeps_the_sign_of_negative_bases() {
+        assert_eq!(X.powf(1.0 / 3.0).eval(-8.0), -2.0);
+        assert_eq!(Function::from(-8.0).powf(1.0 / 3.0), Function::from(-2.0));
+    }
+
+    #[test]
+    fn rebalancing_a_long_sum_makes_it_shallow() {
+        let sum = (1..=1000).fold(Function::from(0.0), |sum, k| sum + k as f64 * X);
+        let balanced = sum.rebalance();
+        assert_eq!(sum.depth(), 1001);
+        assert_eq!(balanced.depth(), 12);
+        for x in [-3.0, 0.5, 2.0] {
+            assert_eq!(balanced.eval(x), sum.eval(x));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn derivative_is_simplified_prime() {
+        let f = X.cos().powf(2.0);
+        let derivative = f.derivative();
+        assert_eq!(derivative.to_string(), "-2 * cos(x) * sin(x)");
+        for x in [-1.0, 0.3, 2.0] {
+            assert!((derivative.eval(x) - f.prime().eval(x)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn eval_shared_visits_each_shared_node_once() {
+        // ten levels of g * g with both sides the same Rc: 4095 nodes as a tree, but only 13 distinct ones
+        let mut f = X + 1.0;
+        for _ in 0..10 {
+            let shared = FunctionRef::new(f);
+            f = Function::Multiply(shared.clone(), shared);
+        }
+        let (plain, plain_count) = evaluations(|| f.eval(0.5));
+        let (shared, shared_count) = evaluations(|| f.eval_shared(0.5));
+        assert_eq!(plain, shared);
+        assert_eq!(plain_count, 3 * 1024 + 1023);
+        assert_eq!(shared_count, f.node_count());
+        assert_eq!(shared_count, 13);
+    }
+
+    #[test]
+    fn eval_to_function_gives_a_constant() {
+        assert_eq!(X.powf(2.0).eval_to_function(3.0), Function::Constant(9.0));
+    }
+
+    #[test]
+    fn precedence_and_arity_metadata() {
+        let sum = X + 1.0;
+        let product = 2.0 * X;
+        let power = X.powf(3.0);
+        assert!(sum.precedence() < product.precedence());
+        assert!(product.precedence() < power.precedence());
+        assert_eq!(X.arity(), 0);
+        assert_eq!(X.sin().arity(), 1);
+        assert_eq!(sum.arity(), 2);
+    }
+
+    #[test]
+    fn prime_rule_names_the_top_level_rule() {
+        assert_eq!(X.powf(2.0).prime_rule(), DiffRule::PowerRule);
+        assert_eq!(X.sin().exp().prime_rule(), DiffRule::ChainRule);
+        assert_eq!((X * X.sin()).prime_rule(), DiffRule::ProductRule);
+        assert_eq!((X.sin() / X).prime_rule(), DiffRule::QuotientRule);
+        assert_eq!((3.0 * X.sin()).prime_rule(), DiffRule::ConstantMultipleRule);
+    }
+
+    #[test]
+    fn elasticity_of_a_power_is_its_exponent() {
+        let elasticity = X.powf(2.5).elasticity();
+        for x in [0.5, 1.0, 3.0, 10.0] {
+            assert!((elasticity.eval(x) - 2.5).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn eval_rounded_rounds_only_the_result() {
+        assert_eq!(X.powf(2.0).eval_rounded(3.1622776, 2), 10.0);
+        assert_eq!(X.powf(0.5).eval_rounded(2.0, 3), 1.414);
+        assert_eq!((1000.0 * X.powf(2.0)).eval_rounded(0.0316, 1), 1.0);
+    }
+
+    #[test]
+    fn square_roots_fold_and_differentiate() {
+        assert_eq!(X.powf(0.5), X.sqrt());
+        assert_eq!(Function::from(9.0).powf(0.5), Function::from(3.0));
+        assert!(X.sqrt().eval(-1.0).is_nan());
+        assert_eq!(crate::parser::parse(&X.sqrt().to_string()).unwrap(), X.sqrt());
+        let derivative = X.sqrt().prime();
+        for x in [0.25f64, 1.0, 2.0, 100.0] {
+            let expected = 1.0 / (2.0 * x.sqrt());
+            assert!((derivative.eval(x) - expected).abs() < 1e-12, "{x}");
+        }
+    }
+
+    #[test]
+    fn inverse_trig_derivatives_match_closed_forms() {
+        for x in [-0.9f64, -0.3, 0.0, 0.5, 0.8] {
+            assert!((X.asin().prime().eval(x) - 1.0 / (1.0 - x * x).sqrt()).abs() < 1e-12, "{x}");
+            assert!((X.acos().prime().eval(x) + 1.0 / (1.0 - x * x).sqrt()).abs() < 1e-12, "{x}");
+            assert!((X.atan().prime().eval(x) - 1.0 / (1.0 + x * x)).abs() < 1e-12, "{x}");
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn sine_evaluates_through_libm_without_std() {
+        let f = X.sin();
+        assert_eq!(f.eval(1.0), libm::sin(1.0));
+        assert_eq!(f.prime().eval(1.0), libm::cos(1.0));
+    }
+
+    #[test]
+    fn constant_over_function_uses_the_quotient_rule() {
+        let reciprocal = (1.0 / X).prime();
+        for x in [-2.0, 0.5, 3.0] {
+            assert!((reciprocal.eval(x) + 1.0 / (x * x)).abs() < 1e-12, "{x}");
+        }
+        let cosecant = (5.0 / X.sin()).prime();
+        for x in [0.5f64, 1.0, 2.0] {
+            let expected = -5.0 * x.cos() / x.sin().powi(2);
+            assert!((cosecant.eval(x) - expected).abs() < 1e-12, "{x}");
+        }
+    }
+
+    #[test]
+    fn general_power_rule_keeps_the_power_factor() {
+        let derivative = X.pow(&X).prime();
+        assert!((derivative.eval(2.0) - 4.0 * (2f64.ln() + 1.0)).abs() < 1e-12);
+        for x in [0.5f64, 1.5, 3.0] {
+            assert!((derivative.eval(x) - x.powf(x) * (x.ln() + 1.0)).abs() < 1e-10, "{x}");
+        }
+    }
+
+    #[test]
+    fn nth_derivative_of_a_quartic() {
+        let quartic = X.powf(4.0);
+        assert_eq!(quartic.nth_derivative(0), quartic);
+        assert_eq!(quartic.nth_derivative(4), Function::from(24.0));
+        assert_eq!(quartic.nth_derivative(1_000_000), Function::from(0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn schwarzian_of_a_mobius_transform_is_zero() {
+        for (a, b, c, d) in [(2.0, 3.0, 4.0, 5.0), (1.0, -1.0, 1.0, 1.0), (0.0, 1.0, 1.0, 0.0), (1.0, 2.0, -1.0, 3.0)] {
+            let mobius = (a * X + b) / (c * X + d);
+            assert_eq!(mobius.schwarzian(), Function::from(0.0), "{mobius}");
+        }
+        assert_eq!(X.powf(3.0).schwarzian().to_string(), "-4 / x ^ 2");
+    }
+
+    #[test]
+    fn powers_of_one_and_e_fold() {
+        assert_eq!(Function::Constant(std::f64::consts::E).pow(&X), X.exp());
+        assert!(matches!(Function::Constant(std::f64::consts::E).pow(&X.sin()), Function::Exp(_)));
+        assert_eq!(Function::from(1.0).pow(&X), Function::from(1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_json_round_trip() {
+        let f = X.cos().powf(2.0);
+        let json = serde_json::to_string(&f).unwrap();
+        let back: Function = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, f);
+        for x in [-1.0, 0.0, 0.4, 2.0] {
+            assert_eq!(back.eval(x), f.eval(x));
+        }
+    }
+
+    #[test]
+    fn named_constants_display_symbolically() {
+        assert_eq!(Function::pi().eval(0.0), std::f64::consts::PI);
+        assert_eq!(Function::e().eval(0.0), std::f64::consts::E);
+        assert_eq!(Function::<f64>::pi().to_string(), "π");
+        assert_eq!((X.sin() / Function::pi()).to_string(), "sin(x) / π");
+        assert_eq!(Function::e().prime(), Function::from(0.0));
+    }
+
+    #[test]
+    fn compose_substitutes_every_x() {
+        let inner = X.powf(2.0);
+        let f = X.sin().compose(&inner);
+        assert_eq!(f, inner.sin());
+        let derivative = f.prime();
+        for x in [-1.0f64, 0.3, 2.0] {
+            assert!((f.eval(x) - (x * x).sin()).abs() < 1e-15);
+            assert!((derivative.eval(x) - 2.0 * x * (x * x).cos()).abs() < 1e-12, "{x}");
+        }
+        assert_eq!(Function::from(3.0).compose(&inner), Function::from(3.0));
+        let nested = (X.exp() + X).ln() * X;
+        assert_eq!(nested.compose(&(X + 1.0)).eval(0.5), nested.eval(1.5));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn shared_derivatives_grow_polynomially() {
+        let f = X.sin() * X.cos();
+        let (eighth, sixteenth) = (f.nth_derivative_shared(8), f.nth_derivative_shared(16));
+        // the unshared eighth derivative has about two thousand nodes; doubling the order at most
+        // quadruples the shared one
+        assert!(eighth.node_count() < 100, "{}", eighth.node_count());
+        assert!(sixteenth.node_count() < 4 * eighth.node_count(), "{}", sixteenth.node_count());
+        for x in [0.0, 0.4, 1.3] {
+            assert!((eighth.eval(x) - f.nth_derivative(8).eval(x)).abs() < 1e-9, "{x}");
+        }
+    }
+
+    #[test]
+    fn eval_many_matches_eval() {
+        let f = X.cos().powf(2.0) + X;
+        let xs = [0.0, 1.0, 2.0];
+        assert_eq!(f.eval_many(&xs), xs.map(|x| f.eval(x)));
+    }
+
+    #[test]
+    fn abs_derivative_is_the_sign() {
+        let f = (X - 1.0).abs();
+        assert_eq!(f.eval(-2.0), 3.0);
+        assert_eq!(f.to_string(), "|x - 1|");
+        assert_eq!(Function::from(-4.0).abs(), Function::from(4.0));
+        let derivative = X.abs().prime();
+        assert_eq!(derivative.eval(2.0), 1.0);
+        assert_eq!(derivative.eval(-2.0), -1.0);
+        assert!(derivative.eval(0.0).is_nan());
+    }
+
+    #[test]
+    fn display_parenthesizes_only_where_precedence_needs_it() {
+        assert_eq!((X + 2.0 * X.powf(2.0) - 3.0).to_string(), "x + 2 * x ^ 2 - 3");
+        assert_eq!((X - (X - 1.0)).to_string(), "x - (x - 1)");
+        // ^ is right-associative
+        assert_eq!(Function::from(2.0).pow(&X.powf(2.0)).to_string(), "2 ^ x ^ 2");
+        assert_eq!(X.pow(&X).pow(&X).to_string(), "(x ^ x) ^ x");
+    }
+
+    #[test]
+    fn canonical_form_ignores_operand_order() {
+        let hash = |f: &Function| {
+            let mut hasher = std::hash::DefaultHasher::new();
+            f.hash(&mut hasher);
+            hasher.finish()
+        };
+        let pairs = [
+            (X + X.sin(), X.sin() + X),
+            ((X + 1.0) * X.exp() * 2.0, 2.0 * (X.exp() * (1.0 + X))),
+            (X + (X.cos() + X.powf(2.0)), (X.powf(2.0) + X) + X.cos()),
+            // a raw square root power and Sqrt used to print alike, which left them in input order
+            (Function::Powi(FunctionRef::new(X), 0.5) + X.sqrt(), X.sqrt() + Function::Powi(FunctionRef::new(X), 0.5)),
+        ];
+        for (a, b) in pairs {
+            assert_ne!(a, b);
+            let (a, b) = (a.canonicalize(), b.canonicalize());
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b));
+        }
+        assert_ne!((X + 1.0).canonicalize(), (X + 2.0).canonicalize());
+    }
+
+    #[test]
+    fn negation_folds_and_cancels() {
+        assert_eq!((-X).eval(3.0), -3.0);
+        assert_eq!((-X).to_string(), "-x");
+        assert_eq!(-(-X.sin()), X.sin());
+        assert_eq!(-&-&X, X);
+        assert_eq!(-Function::from(2.5), Function::from(-2.5));
+    }
+
+    #[test]
+    fn integer_powers_differentiate_exactly() {
+        assert_eq!(X.powf(5.0).nth_derivative(3), 60.0 * X.powf(2.0));
+        assert_eq!(X.powf(3.0).prime(), 3.0 * X.powf(2.0));
+        assert_eq!(X.powf(7.0).eval(-1.5), (-1.5f64).powi(7));
+    }
+
+    #[test]
+    fn try_eval_names_the_domain_error() {
+        assert_eq!((1.0 / X).try_eval(0.0), Err(EvalError::DivisionByZero));
+        assert_eq!(X.ln().try_eval(-1.0), Err(EvalError::LogOfNonPositive));
+        assert_eq!(X.pow(&X.sin()).try_eval(-2.0), Err(EvalError::NegativeBase));
+        assert_eq!(X.pow(&X).try_eval(-2.0), Ok(0.25));
+        assert_eq!((X.sin() / X).try_eval(2.0), Ok(2f64.sin() / 2.0));
+    }
+
+    #[test]
+    fn reciprocal_trig_derivatives() {
+        let (s, c, t) = (1f64.sin(), 1f64.cos(), 1f64.tan());
+        assert!((X.sec().prime().eval(1.0) - t / c).abs() < 1e-12);
+        assert!((X.csc().prime().eval(1.0) + 1.0 / (s * t)).abs() < 1e-12);
+        assert!((X.cot().prime().eval(1.0) + 1.0 / (s * s)).abs() < 1e-12);
+        assert_eq!(X.sec().to_string(), "sec(x)");
+        assert_eq!((X * 2.0).cot().to_string(), "cot(x * 2)");
+    }
+}
@@ -0,0 +1,212 @@
+
+use crate::Function;
+use crate::analysis::roots_in;
+use crate::sample::grid;
+use crate::taylor::taylor_coefficients;
+
+const MAX_DEPTH: u32 = 50;
+
+impl Function {
+    pub fn integrate(&self, a: f64, b: f64, n: u64) -> f64 {
+        if b < a {
+            return -self.integrate(b, a, n);
+        }
+        composite_simpson(self, a, b, n as usize)
+    }
+
+    // only the polynomial shapes; anything else is None rather than a guess
+    pub fn antiderivative(&self) -> Option<Function> {
+        Some(match self {
+            Self::Constant(c) => *c * Function::X,
+            Self::X => Function::X.powf(2.0) / 2.0,
+            Self::Powi(f, n) if **f == Function::X && *n != -1.0 => Function::X.powf(n + 1.0) / (n + 1.0),
+            // x^0.5 is built as Sqrt, so it needs its own arm
+            Self::Sqrt(f) if **f == Function::X => Function::X.powf(1.5) / 1.5,
+            Self::Add(f, g) => f.antiderivative()? + g.antiderivative()?,
+            Self::Subtract(f, g) => f.antiderivative()? - g.antiderivative()?,
+            Self::Multiply(f, g) => match (&**f, &**g) {
+                (Self::Constant(c), h) | (h, Self::Constant(c)) => *c * h.antiderivative()?,
+                _ => return None,
+            },
+            Self::Divide(f, g) => match &**g {
+                Self::Constant(c) => f.antiderivative()? / *c,
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+pub fn adaptive_simpson(f: &Function, a: f64, b: f64, tol: f64) -> (f64, f64) {
+    let (fa, fm, fb) = (f.eval(a), f.eval((a + b) / 2.0), f.eval(b));
+    let whole = simpson(a, b, fa, fm, fb);
+    simpson_step(f, a, b, fa, fm, fb, whole, tol, MAX_DEPTH)
+}
+
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    (b - a) / 6.0 * (fa + 4.0 * fm + fb)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn simpson_step(f: &Function, a: f64, b: f64, fa: f64, fm: f64, fb: f64, whole: f64, tol: f64, depth: u32) -> (f64, f64) {
+    let m = (a + b) / 2.0;
+    let (flm, frm) = (f.eval((a + m) / 2.0), f.eval((m + b) / 2.0));
+    let left = simpson(a, m, fa, flm, fm);
+    let right = simpson(m, b, fm, frm, fb);
+    let delta = left + right - whole;
+    if depth == 0 || delta.abs() <= 15.0 * tol {
+        return (left + right + delta / 15.0, delta.abs() / 15.0);
+    }
+    let (left, left_error) = simpson_step(f, a, m, fa, flm, fm, left, tol / 2.0, depth - 1);
+    let (right, right_error) = simpson_step(f, m, b, fm, frm, fb, right, tol / 2.0, depth - 1);
+    (left + right, left_error + right_error)
+}
+
+pub fn integral_via_taylor(f: &Function, a: f64, b: f64, order: u64) -> f64 {
+    let center = (a + b) / 2.0;
+    taylor_coefficients(order, center, f).iter().enumerate()
+        .map(|(n, c)| {
+            let power = n as i32 + 1;
+            c * ((b - center).powi(power) - (a - center).powi(power)) / power as f64
+        })
+        .sum()
+}
+
+pub fn work(f: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    f.integrate(a, b, samples as u64)
+}
+
+pub fn moment(f: &Function, k: u32, a: f64, b: f64, samples: usize) -> f64 {
+    (Function::X.powf(k as f64) * f).integrate(a, b, samples as u64)
+}
+
+pub fn geometric_mean(f: &Function, a: f64, b: f64, samples: usize) -> Option<f64> {
+    if !grid(a, b, samples).into_iter().all(|x| f.eval(x) > 0.0) {
+        return None;
+    }
+    Some((composite_simpson(&f.ln(), a, b, samples) / (b - a)).exp())
+}
+
+pub fn harmonic_mean(f: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    (b - a) / composite_simpson(&(1.0 / f), a, b, samples)
+}
+
+pub fn area_between(f: &Function, g: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    let difference = f - g;
+    let mut bounds = vec![a];
+    bounds.extend(roots_in(&difference, a, b, samples).into_iter().filter(|&x| x > a && x < b));
+    bounds.push(b);
+    bounds.windows(2).map(|pair| composite_simpson(&difference, pair[0], pair[1], samples).abs()).sum()
+}
+
+// |κ| ds = |f''| / (1 + f'^2)^(3/2) * sqrt(1 + f'^2) dx, and the absolute value is taken piecewise
+// between the zeros of f'' the same way area_between does
+pub fn total_curvature(f: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    let slope = f.prime();
+    let bending = slope.prime() / (1.0 + slope.powf(2.0));
+    area_between(&bending, &Function::from(0.0), a, b, samples)
+}
+
+fn composite_simpson(f: &Function, a: f64, b: f64, samples: usize) -> f64 {
+    // Simpson's rule needs an even number of intervals
+    let intervals = samples.max(2).div_ceil(2) * 2;
+    let h = (b - a) / intervals as f64;
+    let interior: f64 = (1..intervals)
+        .map(|i| if i % 2 == 1 { 4.0 } else { 2.0 } * f.eval(a + i as f64 * h))
+        .sum();
+    h / 3.0 * (f.eval(a) + interior + f.eval(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn adaptive_simpson_meets_a_tight_tolerance() {
+        let f = 1.0 / (1.0 + X.powf(2.0));
+        let (integral, error) = adaptive_simpson(&f, -100.0, 100.0, 1e-10);
+        assert!((integral - 2.0 * 100f64.atan()).abs() < 1e-9, "{integral}");
+        assert!(error < 1e-9, "{error}");
+    }
+
+    #[test]
+    fn gaussian_integral_via_taylor_matches_erf() {
+        // sqrt(pi) / 2 * erf(1)
+        let exact = 0.746824132812427;
+        let integral = integral_via_taylor(&(-1.0 * X.powf(2.0)).exp(), 0.0, 1.0, 10);
+        assert!((integral - exact).abs() < 1e-6, "{integral}");
+    }
+
+    #[test]
+    fn work_is_signed_by_direction() {
+        assert!((work(&X, 0.0, 2.0, 10) - 2.0).abs() < 1e-12);
+        assert!((work(&X, 2.0, 0.0, 10) + 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn moments_of_a_normalized_even_density() {
+        // the standard normal density, truncated where its tails are negligible
+        let density = (-0.5 * X.powf(2.0)).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        assert!((moment(&density, 0, -10.0, 10.0, 400) - 1.0).abs() < 1e-9);
+        assert!(moment(&density, 1, -10.0, 10.0, 400).abs() < 1e-12);
+        assert!((moment(&density, 2, -10.0, 10.0, 400) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn geometric_and_harmonic_means_of_x() {
+        let geometric = geometric_mean(&X, 1.0, 2.0, 200).unwrap();
+        assert!((geometric - 4.0 / std::f64::consts::E).abs() < 1e-10, "{geometric}");
+        let harmonic = harmonic_mean(&X, 1.0, 2.0, 200);
+        assert!((harmonic - 1.0 / 2f64.ln()).abs() < 1e-10, "{harmonic}");
+        assert_eq!(geometric_mean(&X, -1.0, 2.0, 200), None);
+    }
+
+    #[test]
+    fn area_between_a_line_and_a_parabola() {
+        let area = area_between(&X, &X.powf(2.0), 0.0, 1.0, 200);
+        assert!((area - 1.0 / 6.0).abs() < 1e-10, "{area}");
+        // the curves cross at 1, so the two regions are added rather than cancelling
+        let area = area_between(&X, &X.powf(2.0), 0.0, 2.0, 200);
+        assert!((area - 1.0).abs() < 1e-9, "{area}");
+    }
+
+    #[test]
+    fn composite_simpson_on_function() {
+        assert!((X.integrate(0.0, 1.0, 100) - 0.5).abs() < 1e-14);
+        assert!((X.sin().integrate(0.0, std::f64::consts::PI, 1000) - 2.0).abs() < 1e-11);
+        // an odd count is bumped to the next even one
+        assert_eq!(X.powf(3.0).integrate(0.0, 2.0, 7), X.powf(3.0).integrate(0.0, 2.0, 8));
+        assert_eq!(X.exp().integrate(1.0, 0.0, 50), -X.exp().integrate(0.0, 1.0, 50));
+    }
+
+    #[test]
+    fn antiderivative_of_a_polynomial_differentiates_back() {
+        let f = 3.0 * X.powf(2.0) + 2.0 * X;
+        let integral = f.antiderivative().unwrap();
+        assert_eq!(integral.derivative(), f.simplify());
+        for x in [-1.5, 0.0, 2.0] {
+            assert!((integral.prime().eval(x) - f.eval(x)).abs() < 1e-12);
+        }
+        assert_eq!(X.tan().antiderivative(), None);
+    }
+
+    #[test]
+    fn antiderivative_of_a_square_root_is_a_three_halves_power() {
+        let integral = X.powf(0.5).antiderivative().unwrap();
+        assert_eq!(integral, X.powf(1.5) / 1.5);
+        for x in [0.25, 1.0, 4.0] {
+            assert!((integral.prime().eval(x) - x.sqrt()).abs() < 1e-12, "{x}");
+        }
+        assert_eq!((X + 1.0).sqrt().antiderivative(), None);
+    }
+
+    #[test]
+    fn total_curvature_is_the_turning_of_the_tangent() {
+        assert_eq!(total_curvature(&(3.0 * X - 1.0), -2.0, 5.0, 100), 0.0);
+        // the tangent of x^2 turns from atan(-2) to atan(2) over [-1, 1]
+        let turning = total_curvature(&X.powf(2.0), -1.0, 1.0, 400);
+        assert!((turning - 2.0 * 2f64.atan()).abs() < 1e-8, "{turning}");
+    }
+}
@@ -0,0 +1,144 @@
+
+use crate::Function;
+use crate::linalg::least_squares;
+use crate::sample::sample;
+
+pub fn hermite_interpolate(nodes: &[(f64, f64, f64)]) -> Function {
+    // every node appears twice so the divided differences can pick up its slope
+    let z: Vec<f64> = nodes.iter().flat_map(|&(x, _, _)| [x, x]).collect();
+    let mut table: Vec<f64> = nodes.iter().flat_map(|&(_, y, _)| [y, y]).collect();
+    for level in 1..z.len() {
+        for i in (level..z.len()).rev() {
+            table[i] = if z[i] == z[i - level] {
+                nodes[i / 2].2
+            } else {
+                (table[i] - table[i - 1]) / (z[i] - z[i - level])
+            };
+        }
+    }
+    newton_form(&z, &table)
+}
+
+fn newton_form(nodes: &[f64], coefficients: &[f64]) -> Function {
+    let mut polynomial = Function::from(0.0);
+    for (&node, &coefficient) in nodes.iter().zip(coefficients).rev() {
+        polynomial = coefficient + (Function::X - node) * polynomial;
+    }
+    polynomial
+}
+
+pub fn newton_forward_poly(x0: f64, h: f64, samples: &[f64]) -> Function {
+    let nodes: Vec<f64> = (0..samples.len()).map(|k| x0 + k as f64 * h).collect();
+    newton_form(&nodes, &equally_spaced_differences(samples.to_vec(), h))
+}
+
+// samples run up to xn, so the table is the forward one read from the end with a step of -h
+pub fn newton_backward_poly(xn: f64, h: f64, samples: &[f64]) -> Function {
+    let reversed: Vec<f64> = samples.iter().rev().copied().collect();
+    newton_forward_poly(xn, -h, &reversed)
+}
+
+// the k-th forward difference over k! h^k is the divided difference for equally spaced nodes
+fn equally_spaced_differences(mut table: Vec<f64>, h: f64) -> Vec<f64> {
+    let mut coefficients = vec![];
+    let mut scale = 1.0;
+    while let Some(&first) = table.first() {
+        coefficients.push(first / scale);
+        scale *= coefficients.len() as f64 * h;
+        table = table.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    }
+    coefficients
+}
+
+pub fn bernstein_approx(f: &Function, a: f64, b: f64, degree: usize) -> Function {
+    let t = (Function::X - a) / (b - a);
+    let mut binomial = 1.0;
+    let mut polynomial = Function::from(0.0);
+    for (k, (_, control)) in sample(f, a, b, degree + 1).into_iter().enumerate() {
+        let basis = t.powf(k as f64) * (1.0 - &t).powf((degree - k) as f64);
+        polynomial = polynomial + binomial * control * basis;
+        binomial = binomial * (degree - k) as f64 / (k + 1) as f64;
+    }
+    polynomial
+}
+
+// None when the finite samples can't pin down degree + 1 coefficients
+pub fn lsq_poly_fit(f: &Function, a: f64, b: f64, degree: usize, samples: usize) -> Option<Function> {
+    let points: Vec<(f64, f64)> = sample(f, a, b, samples).into_iter().filter(|(_, y)| y.is_finite()).collect();
+    if points.len() <= degree {
+        return None;
+    }
+    let vandermonde = points.iter().map(|&(x, _)| (0..=degree).map(|k| x.powi(k as i32)).collect()).collect();
+    let values = points.iter().map(|&(_, y)| y).collect();
+    least_squares(vandermonde, values).map(|coefficients| Function::from_monomials(&coefficients))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn hermite_matches_values_and_slopes() {
+        let polynomial = hermite_interpolate(&[(0.0, 0.0, 1.0), (1.0, 1.0, 1.0)]);
+        let slope = polynomial.prime();
+        for x in [0.0, 1.0] {
+            assert!((polynomial.eval(x) - x).abs() < 1e-12);
+            assert!((slope.eval(x) - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn cubic_fit_to_exp_is_close() {
+        let f = Function::X.exp();
+        let fit = lsq_poly_fit(&f, 0.0, 1.0, 3, 50).unwrap();
+        let points = sample(&f, 0.0, 1.0, 50);
+        let rms = (points.iter().map(|&(x, y)| (fit.eval(x) - y).powi(2)).sum::<f64>() / points.len() as f64).sqrt();
+        assert!(rms < 1e-3, "{rms}");
+        assert!(fit.is_polynomial());
+    }
+
+    #[test]
+    fn fit_with_too_few_samples_is_none() {
+        assert_eq!(lsq_poly_fit(&Function::X.exp(), 0.0, 1.0, 3, 2), None);
+        assert_eq!(lsq_poly_fit(&Function::X.ln(), -2.0, -1.0, 1, 10), None);
+    }
+
+    #[test]
+    fn degree_eight_fit_to_exp_is_accurate() {
+        let f = Function::X.exp();
+        let fit = lsq_poly_fit(&f, 0.0, 1.0, 8, 50).unwrap();
+        for (x, y) in sample(&f, 0.0, 1.0, 50) {
+            assert!((fit.eval(x) - y).abs() < 1e-10, "{x}");
+        }
+    }
+
+    #[test]
+    fn newton_forms_recover_a_cubic() {
+        let cubic = |x: f64| 2.0 * x.powi(3) - x + 3.0;
+        let samples: Vec<f64> = (0..5).map(|k| cubic(1.0 + 0.5 * k as f64)).collect();
+        let forward = newton_forward_poly(1.0, 0.5, &samples);
+        let backward = newton_backward_poly(3.0, 0.5, &samples);
+        for x in [-1.0, 0.0, 1.7, 2.9, 4.0] {
+            assert!((forward.eval(x) - cubic(x)).abs() < 1e-10, "forward at {x}");
+            assert!((backward.eval(x) - cubic(x)).abs() < 1e-10, "backward at {x}");
+        }
+    }
+
+    #[test]
+    fn bernstein_approximation_converges() {
+        let max_error = |degree| {
+            let approximation = bernstein_approx(&X.sin(), 0.0, 1.0, degree);
+            (0..=20).map(|k| k as f64 / 20.0)
+                .map(|x| (approximation.eval(x) - x.sin()).abs())
+                .fold(0.0, f64::max)
+        };
+        let errors: Vec<f64> = [2, 4, 8, 16, 32].into_iter().map(max_error).collect();
+        assert!(errors.windows(2).all(|pair| pair[1] < pair[0]), "{errors:?}");
+        let constant = bernstein_approx(&Function::from(2.5), -1.0, 3.0, 6);
+        for x in [-1.0, 0.0, 1.7, 3.0] {
+            assert!((constant.eval(x) - 2.5).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+
+use crate::Function;
+use crate::function::real_pow;
+use crate::math;
+use crate::taylor::multiply_series;
+
+impl Function {
+    // [f(x), f'(x)/1!, f''(x)/2!, ...] from one pass of truncated power-series arithmetic
+    pub fn jet(&self, x: f64, order: usize) -> Vec<f64> {
+        let n = order + 1;
+        match self {
+            Self::Constant(a) => constant(*a, n),
+            Self::X => {
+                let mut series = constant(x, n);
+                if n > 1 {
+                    series[1] = 1.0;
+                }
+                series
+            }
+            Self::Add(a, b) => combine(&a.jet(x, order), &b.jet(x, order), 1.0),
+            Self::Subtract(a, b) => combine(&a.jet(x, order), &b.jet(x, order), -1.0),
+            Self::Multiply(a, b) => multiply_series(&a.jet(x, order), &b.jet(x, order), order),
+            Self::Divide(a, b) => divide(&a.jet(x, order), &b.jet(x, order)),
+            Self::Powi(a, p) => power(&a.jet(x, order), *p),
+            Self::Powa(a, b) => exp(&scale(&b.jet(x, order), math::ln(*a))),
+            Self::Pow(a, b) => exp(&multiply_series(&b.jet(x, order), &ln(&a.jet(x, order)), order)),
+            Self::Sqrt(a) => power(&a.jet(x, order), 0.5),
+            Self::Exp(a) => exp(&a.jet(x, order)),
+            Self::Ln(a) => ln(&a.jet(x, order)),
+            Self::Sin(a) => sin_cos(&a.jet(x, order), -1.0).0,
+            Self::Cos(a) => sin_cos(&a.jet(x, order), -1.0).1,
+            Self::Tan(a) => {
+                let (sin, cos) = sin_cos(&a.jet(x, order), -1.0);
+                divide(&sin, &cos)
+            }
+            Self::Asin(a) => {
+                let a = a.jet(x, order);
+                integrate(math::asin(a[0]), &a, &power(&one_minus_square(&a, 1.0), -0.5))
+            }
+            Self::Acos(a) => {
+                let a = a.jet(x, order);
+                integrate(math::acos(a[0]), &a, &scale(&power(&one_minus_square(&a, 1.0), -0.5), -1.0))
+            }
+            Self::Atan(a) => {
+                let a = a.jet(x, order);
+                integrate(math::atan(a[0]), &a, &power(&one_minus_square(&a, -1.0), -1.0))
+            }
+            Self::Sinh(a) => sin_cos(&a.jet(x, order), 1.0).0,
+            Self::Cosh(a) => sin_cos(&a.jet(x, order), 1.0).1,
+            Self::Tanh(a) => {
+                let (sinh, cosh) = sin_cos(&a.jet(x, order), 1.0);
+                divide(&sinh, &cosh)
+            }
+            // like prime, everything past the value is NaN at the kink
+            Self::Abs(a) => {
+                let a = a.jet(x, order);
+                let mut series = scale(&a, a[0] / a[0].abs());
+                series[0] = a[0].abs();
+                series
+            }
+            Self::Erf(a) => {
+                let a = a.jet(x, order);
+                let gaussian = scale(&exp(&scale(&multiply_series(&a, &a, order), -1.0)), std::f64::consts::FRAC_2_SQRT_PI);
+                integrate(math::erf(a[0]), &a, &gaussian)
+            }
+        }
+    }
+}
+
+fn constant(a: f64, n: usize) -> Vec<f64> {
+    let mut series = vec![0.0; n];
+    series[0] = a;
+    series
+}
+
+fn combine(a: &[f64], b: &[f64], sign: f64) -> Vec<f64> {
+    a.iter().zip(b).map(|(x, y)| x + sign * y).collect()
+}
+
+fn scale(a: &[f64], factor: f64) -> Vec<f64> {
+    a.iter().map(|c| c * factor).collect()
+}
+
+fn divide(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut quotient: Vec<f64> = vec![];
+    for n in 0..a.len() {
+        let sum: f64 = (1..= n).map(|k| b[k] * quotient[n - k]).sum();
+        quotient.push((a[n] - sum) / b[0]);
+    }
+    quotient
+}
+
+// the recurrence divides by a[0], so non-negative integer powers multiply instead to stay exact at zero
+fn power(a: &[f64], p: f64) -> Vec<f64> {
+    let order = a.len() - 1;
+    if p.fract() == 0.0 && p >= 0.0 {
+        return (0..p as u64).fold(constant(1.0, a.len()), |acc, _| multiply_series(&acc, a, order));
+    }
+    let mut series = vec![real_pow(a[0], p)];
+    for n in 1..a.len() {
+        let sum: f64 = (1..= n).map(|k| (p * k as f64 - (n - k) as f64) * a[k] * series[n - k]).sum();
+        series.push(sum / (n as f64 * a[0]));
+    }
+    series
+}
+
+fn exp(a: &[f64]) -> Vec<f64> {
+    let mut series = vec![math::exp(a[0])];
+    for n in 1..a.len() {
+        let sum: f64 = (1..= n).map(|k| k as f64 * a[k] * series[n - k]).sum();
+        series.push(sum / n as f64);
+    }
+    series
+}
+
+fn ln(a: &[f64]) -> Vec<f64> {
+    let mut series = vec![math::ln(a[0])];
+    for n in 1..a.len() {
+        let sum: f64 = (1..n).map(|k| k as f64 * series[k] * a[n - k]).sum();
+        series.push((a[n] - sum / n as f64) / a[0]);
+    }
+    series
+}
+
+// sign -1 gives (sin, cos) and sign 1 gives (sinh, cosh), since cos' = -sin but cosh' = sinh
+fn sin_cos(a: &[f64], sign: f64) -> (Vec<f64>, Vec<f64>) {
+    let (mut sin, mut cos) = if sign < 0.0 {
+        (vec![math::sin(a[0])], vec![math::cos(a[0])])
+    } else {
+        (vec![math::sinh(a[0])], vec![math::cosh(a[0])])
+    };
+    for n in 1..a.len() {
+        let s: f64 = (1..= n).map(|k| k as f64 * a[k] * cos[n - k]).sum();
+        let c: f64 = (1..= n).map(|k| k as f64 * a[k] * sin[n - k]).sum();
+        sin.push(s / n as f64);
+        cos.push(sign * c / n as f64);
+    }
+    (sin, cos)
+}
+
+// 1 - a^2 for sign 1 and 1 + a^2 for sign -1
+fn one_minus_square(a: &[f64], sign: f64) -> Vec<f64> {
+    let mut series = scale(&multiply_series(a, a, a.len() - 1), -sign);
+    series[0] += 1.0;
+    series
+}
+
+// the series of h with h(x) = value and h' = a' * g
+fn integrate(value: f64, a: &[f64], g: &[f64]) -> Vec<f64> {
+    let slope: Vec<f64> = (1..a.len()).map(|k| k as f64 * a[k]).chain([0.0]).collect();
+    let derivative = multiply_series(&slope, g, a.len() - 1);
+    let mut series = vec![value];
+    series.extend((1..a.len()).map(|n| derivative[n - 1] / n as f64));
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taylor::{factorial, taylor_coefficients};
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn jets_are_the_local_taylor_coefficients() {
+        // every derivative of exp is 1 at 0, so the coefficients are 1 / n!
+        for (n, c) in X.exp().jet(0.0, 8).into_iter().enumerate() {
+            assert!((c * factorial(n as u64) - 1.0).abs() < 1e-14, "coefficient {n} is {c}");
+        }
+        // x^3 = 1 + 3 (x - 1) + 3 (x - 1)^2 + (x - 1)^3
+        assert_eq!(X.powf(3.0).jet(1.0, 5), [1.0, 3.0, 3.0, 1.0, 0.0, 0.0]);
+        let f = X.sin() / (X.powf(2.0) + 1.0);
+        for (n, (c, expected)) in f.jet(0.7, 6).iter().zip(taylor_coefficients(6, 0.7, &f)).enumerate() {
+            assert!((c - expected).abs() < 1e-12, "coefficient {n}: {c} vs {expected}");
+        }
+    }
+}
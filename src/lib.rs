@@ -0,0 +1,40 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("without the std feature, the libm feature is needed for the transcendentals");
+
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+pub mod compile;
+#[cfg(feature = "std")]
+pub mod complex;
+pub mod function;
+#[cfg(feature = "std")]
+pub mod integrate;
+#[cfg(feature = "std")]
+pub mod interpolate;
+#[cfg(feature = "std")]
+pub mod jet;
+#[cfg(feature = "std")]
+pub mod linalg;
+pub mod math;
+pub mod parser;
+#[cfg(feature = "std")]
+pub mod polynomial;
+#[cfg(feature = "random")]
+pub mod random;
+#[cfg(feature = "std")]
+pub mod render;
+pub mod rpn;
+#[cfg(feature = "std")]
+pub mod sample;
+#[cfg(feature = "std")]
+pub mod simplify;
+#[cfg(feature = "std")]
+pub mod taylor;
+
+pub use function::Function;
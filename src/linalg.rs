@@ -0,0 +1,246 @@
+
+pub fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| matrix[i][col].abs().total_cmp(&matrix[j][col].abs()))?;
+        if matrix[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+        let pivot_row = matrix[col].clone();
+        for row in col + 1..n {
+            let factor = matrix[row][col] / pivot_row[col];
+            for (entry, pivot_entry) in matrix[row].iter_mut().zip(&pivot_row).skip(col) {
+                *entry -= factor * pivot_entry;
+            }
+            rhs[row] -= factor * rhs[col];
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - tail) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+// least-squares solution of an overdetermined system by Householder QR, which works on the matrix itself
+// rather than on the normal equations that square its condition number
+pub fn least_squares(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let (m, n) = (matrix.len(), matrix.first().map_or(0, Vec::len));
+    if m < n {
+        return None;
+    }
+    for col in 0..n {
+        let norm = matrix[col..].iter().map(|row| row[col].powi(2)).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        // reflect the column onto -sign * norm * e_col, the choice that avoids cancellation
+        let mut v: Vec<f64> = matrix[col..].iter().map(|row| row[col]).collect();
+        v[0] += norm.copysign(v[0]);
+        let vv: f64 = v.iter().map(|e| e * e).sum();
+        for j in col..n {
+            let factor = 2.0 * matrix[col..].iter().zip(&v).map(|(row, e)| row[j] * e).sum::<f64>() / vv;
+            for (row, e) in matrix[col..].iter_mut().zip(&v) {
+                row[j] -= factor * e;
+            }
+        }
+        let factor = 2.0 * rhs[col..].iter().zip(&v).map(|(r, e)| r * e).sum::<f64>() / vv;
+        for (r, e) in rhs[col..].iter_mut().zip(&v) {
+            *r -= factor * e;
+        }
+    }
+    let largest = (0..n).map(|i| matrix[i][i].abs()).fold(0.0, f64::max);
+    if (0..n).any(|i| matrix[i][i].abs() <= 1e-12 * largest) {
+        return None;
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - tail) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+pub fn determinant(mut matrix: Vec<Vec<f64>>) -> f64 {
+    let n = matrix.len();
+    let mut det = 1.0;
+    for col in 0..n {
+        let pivot = match (col..n).max_by(|&i, &j| matrix[i][col].abs().total_cmp(&matrix[j][col].abs())) {
+            Some(pivot) => pivot,
+            None => return 0.0,
+        };
+        if matrix[pivot][col] == 0.0 {
+            return 0.0;
+        }
+        if pivot != col {
+            matrix.swap(col, pivot);
+            det = -det;
+        }
+        det *= matrix[col][col];
+        let pivot_row = matrix[col].clone();
+        for row in matrix.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (entry, pivot_entry) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    det
+}
+
+// Francis double-shift QR on an upper Hessenberg matrix, returning (re, im) pairs
+pub fn hessenberg_eigenvalues(mut a: Vec<Vec<f64>>) -> Option<Vec<(f64, f64)>> {
+    let n = a.len() as isize;
+    let mut eigenvalues = vec![(0.0, 0.0); n as usize];
+    macro_rules! at {
+        ($i:expr, $j:expr) => { a[($i) as usize][($j) as usize] };
+    }
+    let mut norm = 0.0;
+    for i in 0..n {
+        for j in (i - 1).max(0)..n {
+            norm += at!(i, j).abs();
+        }
+    }
+    let mut nn = n - 1;
+    let mut shift = 0.0;
+    while nn >= 0 {
+        let mut its = 0;
+        loop {
+            let mut l = nn;
+            while l >= 1 {
+                let mut s = at!(l - 1, l - 1).abs() + at!(l, l).abs();
+                if s == 0.0 {
+                    s = norm;
+                }
+                if at!(l, l - 1).abs() + s == s {
+                    at!(l, l - 1) = 0.0;
+                    break;
+                }
+                l -= 1;
+            }
+            let mut x = at!(nn, nn);
+            if l == nn {
+                eigenvalues[nn as usize] = (x + shift, 0.0);
+                nn -= 1;
+            } else {
+                let mut y = at!(nn - 1, nn - 1);
+                let mut w = at!(nn, nn - 1) * at!(nn - 1, nn);
+                if l == nn - 1 {
+                    let p = 0.5 * (y - x);
+                    let q = p * p + w;
+                    let z = q.abs().sqrt();
+                    x += shift;
+                    if q >= 0.0 {
+                        let z = p + z.copysign(p);
+                        let second = if z != 0.0 { x - w / z } else { x + z };
+                        eigenvalues[nn as usize - 1] = (x + z, 0.0);
+                        eigenvalues[nn as usize] = (second, 0.0);
+                    } else {
+                        eigenvalues[nn as usize - 1] = (x + p, -z);
+                        eigenvalues[nn as usize] = (x + p, z);
+                    }
+                    nn -= 2;
+                } else {
+                    if its == 60 {
+                        return None;
+                    }
+                    if its == 10 || its == 20 {
+                        shift += x;
+                        for i in 0..=nn {
+                            at!(i, i) -= x;
+                        }
+                        let s = at!(nn, nn - 1).abs() + at!(nn - 1, nn - 2).abs();
+                        x = 0.75 * s;
+                        y = x;
+                        w = -0.4375 * s * s;
+                    }
+                    its += 1;
+                    let (mut p, mut q, mut r, mut z);
+                    let mut m = nn - 2;
+                    loop {
+                        z = at!(m, m);
+                        r = x - z;
+                        let s = y - z;
+                        p = (r * s - w) / at!(m + 1, m) + at!(m, m + 1);
+                        q = at!(m + 1, m + 1) - z - r - s;
+                        r = at!(m + 2, m + 1);
+                        let s = p.abs() + q.abs() + r.abs();
+                        p /= s;
+                        q /= s;
+                        r /= s;
+                        if m == l {
+                            break;
+                        }
+                        let u = at!(m, m - 1).abs() * (q.abs() + r.abs());
+                        let v = p.abs() * (at!(m - 1, m - 1).abs() + z.abs() + at!(m + 1, m + 1).abs());
+                        if u + v == v {
+                            break;
+                        }
+                        m -= 1;
+                    }
+                    for i in m + 2..=nn {
+                        at!(i, i - 2) = 0.0;
+                        if i != m + 2 {
+                            at!(i, i - 3) = 0.0;
+                        }
+                    }
+                    for k in m..nn {
+                        if k != m {
+                            p = at!(k, k - 1);
+                            q = at!(k + 1, k - 1);
+                            r = if k != nn - 1 { at!(k + 2, k - 1) } else { 0.0 };
+                            x = p.abs() + q.abs() + r.abs();
+                            if x != 0.0 {
+                                p /= x;
+                                q /= x;
+                                r /= x;
+                            }
+                        }
+                        let s = (p * p + q * q + r * r).sqrt().copysign(p);
+                        if s == 0.0 {
+                            continue;
+                        }
+                        if k == m {
+                            if l != m {
+                                at!(k, k - 1) = -at!(k, k - 1);
+                            }
+                        } else {
+                            at!(k, k - 1) = -s * x;
+                        }
+                        p += s;
+                        x = p / s;
+                        y = q / s;
+                        z = r / s;
+                        q /= p;
+                        r /= p;
+                        for j in k..=nn {
+                            let mut p = at!(k, j) + q * at!(k + 1, j);
+                            if k != nn - 1 {
+                                p += r * at!(k + 2, j);
+                                at!(k + 2, j) -= p * z;
+                            }
+                            at!(k + 1, j) -= p * y;
+                            at!(k, j) -= p * x;
+                        }
+                        for i in l..=nn.min(k + 3) {
+                            let mut p = x * at!(i, k) + y * at!(i, k + 1);
+                            if k != nn - 1 {
+                                p += z * at!(i, k + 2);
+                                at!(i, k + 2) -= p * r;
+                            }
+                            at!(i, k + 1) -= p * q;
+                            at!(i, k) -= p;
+                        }
+                    }
+                }
+            }
+            if l >= nn - 1 {
+                break;
+            }
+        }
+    }
+    Some(eigenvalues)
+}
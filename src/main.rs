@@ -1,7 +1,4 @@
-mod function;
-mod taylor;
-
-use function::Function::{self, X};
+use derivative_taylor_finder::{taylor, Function::X};
 
 fn main() {
     let f = X.cos().powf(2.0);
@@ -9,6 +6,7 @@ fn main() {
     let center = 0.0;
     let taylor = taylor::taylor(degree, center, &f);
     println!("f(x) = {f}");
+    println!("f'(x) = {}", f.derivative());
     println!("T<{degree}, {center}>(x) = {taylor}");
     let x = 1.0;
     println!("f({x}) = {}", f.eval(x));
@@ -0,0 +1,140 @@
+use core::fmt::{Debug, Display};
+
+use num_traits::FloatConst;
+
+// the precisions a Function can be built over
+pub trait Float: num_traits::Float + FloatConst + Transcendental + Display + Debug {
+    fn of(a: f64) -> Self;
+}
+
+impl Float for f64 {
+    fn of(a: f64) -> Self {
+        a
+    }
+}
+
+impl Float for f32 {
+    fn of(a: f64) -> Self {
+        a as f32
+    }
+}
+
+// with the `libm` feature every transcendental goes through libm, so results don't depend on the platform's libm
+macro_rules! unary {
+    ($($name:ident => $libm:ident, $libmf:ident),*) => {
+        pub trait Transcendental: Sized {
+            $(fn $name(self) -> Self;)*
+            fn powf(self, y: Self) -> Self;
+            fn erf(self) -> Self;
+        }
+
+        impl Transcendental for f64 {
+            $(
+                #[cfg(feature = "libm")]
+                fn $name(self) -> Self {
+                    libm::$libm(self)
+                }
+
+                #[cfg(not(feature = "libm"))]
+                fn $name(self) -> Self {
+                    f64::$name(self)
+                }
+            )*
+
+            #[cfg(feature = "libm")]
+            fn powf(self, y: Self) -> Self {
+                libm::pow(self, y)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            fn powf(self, y: Self) -> Self {
+                f64::powf(self, y)
+            }
+
+            #[cfg(feature = "libm")]
+            fn erf(self) -> Self {
+                libm::erf(self)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            fn erf(self) -> Self {
+                erf_fallback(self)
+            }
+        }
+
+        impl Transcendental for f32 {
+            $(
+                #[cfg(feature = "libm")]
+                fn $name(self) -> Self {
+                    libm::$libmf(self)
+                }
+
+                #[cfg(not(feature = "libm"))]
+                fn $name(self) -> Self {
+                    f32::$name(self)
+                }
+            )*
+
+            #[cfg(feature = "libm")]
+            fn powf(self, y: Self) -> Self {
+                libm::powf(self, y)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            fn powf(self, y: Self) -> Self {
+                f32::powf(self, y)
+            }
+
+            #[cfg(feature = "libm")]
+            fn erf(self) -> Self {
+                libm::erff(self)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            fn erf(self) -> Self {
+                erf_fallback(self as f64) as f32
+            }
+        }
+
+        $(
+            pub fn $name<T: Float>(x: T) -> T {
+                Transcendental::$name(x)
+            }
+        )*
+    };
+}
+
+unary!(sqrt => sqrt, sqrtf, cbrt => cbrt, cbrtf, exp => exp, expf, ln => log, logf, sin => sin, sinf,
+    cos => cos, cosf, tan => tan, tanf, asin => asin, asinf, acos => acos, acosf, atan => atan, atanf,
+    sinh => sinh, sinhf, cosh => cosh, coshf, tanh => tanh, tanhf);
+
+pub fn powf<T: Float>(x: T, y: T) -> T {
+    Transcendental::powf(x, y)
+}
+
+pub fn erf<T: Float>(x: T) -> T {
+    Transcendental::erf(x)
+}
+
+// std has no erf. Below 3 the series 2/sqrt(pi) e^(-x^2) sum 2^n x^(2n+1) / (1*3*...*(2n+1)) has only positive
+// terms; above it the continued fraction for erfc converges quickly, and past 6 erf is 1 to within an ulp.
+#[cfg(not(feature = "libm"))]
+fn erf_fallback(x: f64) -> f64 {
+    let a = x.abs();
+    if a >= 6.0 {
+        return x.signum();
+    }
+    if a >= 3.0 {
+        let fraction = (1..=60).rev().fold(a, |f, k| a + k as f64 / 2.0 / f);
+        return x.signum() * (1.0 - (-a * a).exp() / std::f64::consts::PI.sqrt() / fraction);
+    }
+    let (mut term, mut sum) = (x, x);
+    for n in 1..100 {
+        term *= 2.0 * x * x / (2 * n + 1) as f64;
+        sum += term;
+        if term.abs() <= f64::EPSILON * sum.abs() {
+            break;
+        }
+    }
+    std::f64::consts::FRAC_2_SQRT_PI * (-x * x).exp() * sum
+}
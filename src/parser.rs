@@ -0,0 +1,236 @@
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use crate::Function;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ParseError {
+    UnexpectedEnd,
+    UnexpectedCharacter(char, usize),
+    UnclosedParen(usize),
+    UnclosedBar(usize),
+    UnmatchedParen(usize),
+    UnknownIdentifier(String, usize),
+    InvalidNumber(String, usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of input"),
+            Self::UnexpectedCharacter(c, at) => write!(f, "unexpected '{}' at position {}", c, at),
+            Self::UnclosedParen(at) => write!(f, "'(' at position {} is never closed", at),
+            Self::UnclosedBar(at) => write!(f, "'|' at position {} is never closed", at),
+            Self::UnmatchedParen(at) => write!(f, "')' at position {} has no matching '('", at),
+            Self::UnknownIdentifier(name, at) => write!(f, "unknown identifier '{}' at position {}", name, at),
+            Self::InvalidNumber(number, at) => write!(f, "invalid number '{}' at position {}", number, at),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+impl Function {
+    pub fn parse(input: &str) -> Result<Function, ParseError> {
+        parse(input)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Function, ParseError> {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0 };
+    let f = parser.expression()?;
+    match parser.peek() {
+        None => Ok(f),
+        Some(')') => Err(ParseError::UnmatchedParen(parser.pos)),
+        Some(c) => Err(ParseError::UnexpectedCharacter(c, parser.pos)),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expression(&mut self) -> Result<Function, ParseError> {
+        let mut f = self.term()?;
+        loop {
+            if self.eat('+') {
+                f = f + self.term()?;
+            } else if self.eat('-') {
+                f = f - self.term()?;
+            } else {
+                return Ok(f);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Function, ParseError> {
+        let mut f = self.unary()?;
+        loop {
+            if self.eat('*') {
+                f = f * self.unary()?;
+            } else if self.eat('/') {
+                f = f / self.unary()?;
+            } else {
+                return Ok(f);
+            }
+        }
+    }
+
+    // -x^2 is -(x^2), and the exponent itself may be negated as in x^-2
+    fn unary(&mut self) -> Result<Function, ParseError> {
+        if self.eat('-') {
+            return Ok(-self.unary()?);
+        }
+        self.power()
+    }
+
+    // ^ is right-associative, so the exponent is parsed as a whole unary expression
+    fn power(&mut self) -> Result<Function, ParseError> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            return Ok(base.pow(&self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Function, ParseError> {
+        let next = self.peek();
+        let start = self.pos;
+        match next {
+            None => Err(ParseError::UnexpectedEnd),
+            Some('(') => self.parenthesized(),
+            Some('|') => self.absolute_value(start),
+            Some(')') => Err(ParseError::UnmatchedParen(start)),
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(start),
+            Some(c) if c.is_alphabetic() => self.identifier(start),
+            Some(c) => Err(ParseError::UnexpectedCharacter(c, start)),
+        }
+    }
+
+    fn parenthesized(&mut self) -> Result<Function, ParseError> {
+        match self.peek() {
+            Some('(') => {}
+            Some(c) => return Err(ParseError::UnexpectedCharacter(c, self.pos)),
+            None => return Err(ParseError::UnexpectedEnd),
+        }
+        let open = self.pos;
+        self.pos += 1;
+        let f = self.expression()?;
+        if !self.eat(')') {
+            return Err(ParseError::UnclosedParen(open));
+        }
+        Ok(f)
+    }
+
+    fn absolute_value(&mut self, open: usize) -> Result<Function, ParseError> {
+        self.pos += 1;
+        let f = self.expression()?;
+        if !self.eat('|') {
+            return Err(ParseError::UnclosedBar(open));
+        }
+        Ok(f.abs())
+    }
+
+    fn number(&mut self, start: usize) -> Result<Function, ParseError> {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+            self.pos += 1;
+        }
+        let literal: String = self.chars[start..self.pos].iter().collect();
+        literal.parse().map(Function::Constant).map_err(|_| ParseError::InvalidNumber(literal, start))
+    }
+
+    fn identifier(&mut self, start: usize) -> Result<Function, ParseError> {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_alphanumeric()) {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        let call: fn(&Function) -> Function = match name.as_str() {
+            "x" => return Ok(Function::X),
+            "e" => return Ok(Function::e()),
+            "pi" | "π" => return Ok(Function::pi()),
+            // Display writes non-finite constants this way
+            "inf" => return Ok(Function::Constant(f64::INFINITY)),
+            "NaN" => return Ok(Function::Constant(f64::NAN)),
+            "sqrt" => Function::sqrt,
+            "cbrt" => |f| f.powf(1.0 / 3.0),
+            "exp" => Function::exp,
+            "ln" => Function::ln,
+            "sin" => Function::sin,
+            "cos" => Function::cos,
+            "tan" => Function::tan,
+            "sec" => Function::sec,
+            "csc" => Function::csc,
+            "cot" => Function::cot,
+            "asin" => Function::asin,
+            "acos" => Function::acos,
+            "atan" => Function::atan,
+            "sinh" => Function::sinh,
+            "cosh" => Function::cosh,
+            "tanh" => Function::tanh,
+            "abs" => Function::abs,
+            "erf" => Function::erf,
+            _ => return Err(ParseError::UnknownIdentifier(name, start)),
+        };
+        Ok(call(&self.parenthesized()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn parse_respects_precedence_and_right_associativity() {
+        assert_eq!(parse("cos(x)^2 + 3*x").unwrap(), X.cos().powf(2.0) + 3.0 * X);
+        assert_eq!(parse("2^3^2").unwrap().eval(0.0), 512.0);
+        assert_eq!(parse("1 - x - x").unwrap().eval(1.0), -1.0);
+        assert_eq!(parse("8 / x / 2").unwrap().eval(2.0), 2.0);
+    }
+
+    #[test]
+    fn parse_reports_the_position_of_errors() {
+        assert_eq!(parse("(x + 1"), Err(ParseError::UnclosedParen(0)));
+        assert_eq!(parse("x + 1)"), Err(ParseError::UnmatchedParen(5)));
+        assert_eq!(parse("2 * foo(x)"), Err(ParseError::UnknownIdentifier("foo".to_string(), 4)));
+        assert_eq!(parse("x +"), Err(ParseError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        let functions = vec![
+            X.cos().powf(2.0) + 3.0 * X,
+            (X - 1.0) / (X.powf(2.0) + 1.0),
+            -2.0 * X.exp() - (X - X.sin()),
+            Function::from(2.0).pow(&(X * X.ln())),
+            X.pow(&X.tanh()).sqrt() + X.abs().powf(1.0 / 3.0),
+            X.sec() * X.atan().erf() / -X.cosh(),
+        ];
+        for f in functions {
+            let parsed = parse(&f.to_string()).unwrap();
+            for x in [0.3, 1.0, 2.5] {
+                assert!((parsed.eval(x) - f.eval(x)).abs() < 1e-12, "{f} at {x}");
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+
+use crate::Function;
+use crate::linalg::hessenberg_eigenvalues;
+
+impl Function {
+    pub fn from_monomials(coefficients: &[f64]) -> Function {
+        let mut polynomial = Function::from(0.0);
+        for (degree, &coefficient) in coefficients.iter().enumerate().rev() {
+            if coefficient == 0.0 {
+                continue;
+            }
+            polynomial = if coefficient < 0.0 && polynomial != Function::Constant(0.0) {
+                polynomial - -coefficient * Function::X.powf(degree as f64)
+            } else {
+                polynomial + coefficient * Function::X.powf(degree as f64)
+            };
+        }
+        polynomial
+    }
+
+    pub fn from_roots(roots: &[f64], leading: f64) -> Function {
+        roots.iter().fold(Function::from(leading), |product, &root| {
+            let factor = if root < 0.0 { Function::X + -root } else { Function::X - root };
+            product * factor
+        })
+    }
+
+    pub fn polynomial_coefficients(&self) -> Option<Vec<f64>> {
+        self.as_polynomial(0.0)
+    }
+
+    // coefficients[n] is the coefficient of (x - center)^n, so a Taylor polynomial about center comes back exactly
+    pub fn as_polynomial(&self, center: f64) -> Option<Vec<f64>> {
+        let coefficients = match self {
+            Self::Constant(a) => vec![*a],
+            Self::X => vec![center, 1.0],
+            Self::Add(a, b) => add(&a.as_polynomial(center)?, &b.as_polynomial(center)?, 1.0),
+            Self::Subtract(a, b) => add(&a.as_polynomial(center)?, &b.as_polynomial(center)?, -1.0),
+            Self::Multiply(a, b) => multiply(&a.as_polynomial(center)?, &b.as_polynomial(center)?),
+            Self::Divide(a, b) => match b.as_polynomial(center)?.as_slice() {
+                [divisor] => a.as_polynomial(center)?.iter().map(|c| c / divisor).collect(),
+                _ => return None,
+            },
+            Self::Powi(a, n) if n.fract() == 0.0 && *n >= 0.0 => {
+                let base = a.as_polynomial(center)?;
+                (0..*n as u64).fold(vec![1.0], |acc, _| multiply(&acc, &base))
+            }
+            Self::Powi(a, _) | Self::Powa(_, a) | Self::Sqrt(a) | Self::Exp(a) | Self::Ln(a)
+                | Self::Sin(a) | Self::Cos(a) | Self::Tan(a) | Self::Asin(a) | Self::Acos(a) | Self::Atan(a)
+                | Self::Sinh(a) | Self::Cosh(a) | Self::Tanh(a) | Self::Abs(a) | Self::Erf(a) => {
+                constant_only(&[a.as_polynomial(center)?], self)?
+            }
+            Self::Pow(a, b) => constant_only(&[a.as_polynomial(center)?, b.as_polynomial(center)?], self)?,
+        };
+        Some(trim(coefficients))
+    }
+
+    pub fn is_polynomial(&self) -> bool {
+        self.polynomial_coefficients().is_some()
+    }
+
+    // None for the zero polynomial too, since every x is a root and there's no list to give
+    pub fn polynomial_roots(&self) -> Option<Vec<f64>> {
+        let coefficients = self.polynomial_coefficients()?;
+        let degree = coefficients.len() - 1;
+        if degree == 0 {
+            return (coefficients[0] != 0.0).then(Vec::new);
+        }
+        let leading = coefficients[degree];
+        let mut companion = vec![vec![0.0; degree]; degree];
+        for (j, c) in coefficients[..degree].iter().rev().enumerate() {
+            companion[0][j] = -c / leading;
+        }
+        for i in 1..degree {
+            companion[i][i - 1] = 1.0;
+        }
+        let mut roots: Vec<f64> = hessenberg_eigenvalues(companion)?.into_iter()
+            .filter(|(re, im)| im.abs() <= 1e-9 * (1.0 + re.abs()))
+            .map(|(re, _)| polish_root(&coefficients, re))
+            .collect();
+        roots.sort_by(f64::total_cmp);
+        Some(roots)
+    }
+
+    pub fn to_standard_polynomial(&self) -> Option<Function> {
+        Some(Function::from_monomials(&self.polynomial_coefficients()?))
+    }
+
+    pub fn to_monic(&self) -> Option<Function> {
+        let coefficients = self.polynomial_coefficients()?;
+        let leading = *coefficients.last()?;
+        if leading == 0.0 {
+            return None;
+        }
+        Some(Function::from_monomials(&coefficients.iter().map(|c| c / leading).collect::<Vec<_>>()))
+    }
+
+    // a^2 x^2 + 2ab x + b^2 becomes (ax + b)^2; anything else is returned unchanged
+    pub fn factor_perfect_square(&self) -> Function {
+        let coefficients = self.polynomial_coefficients().unwrap_or_default();
+        let [c, b, a] = coefficients[..] else { return self.clone() };
+        if a <= 0.0 {
+            return self.clone();
+        }
+        let p = a.sqrt();
+        let q = b / (2.0 * p);
+        if (q * q - c).abs() > 1e-12 * c.abs().max(1.0) {
+            return self.clone();
+        }
+        let linear = if q < 0.0 { p * Function::X - -q } else { p * Function::X + q };
+        linear.powf(2.0)
+    }
+}
+
+fn polish_root(coefficients: &[f64], mut root: f64) -> f64 {
+    for _ in 0..3 {
+        let (value, slope) = coefficients.iter().rev()
+            .fold((0.0, 0.0), |(value, slope), &c| (value * root + c, slope * root + value));
+        if slope == 0.0 || !(value / slope).is_finite() {
+            break;
+        }
+        root -= value / slope;
+    }
+    root
+}
+
+fn constant_only(children: &[Vec<f64>], f: &Function) -> Option<Vec<f64>> {
+    children.iter().all(|c| trim(c.clone()).len() == 1).then(|| vec![f.eval(0.0)])
+}
+
+fn trim(mut coefficients: Vec<f64>) -> Vec<f64> {
+    while coefficients.len() > 1 && coefficients.last() == Some(&0.0) {
+        coefficients.pop();
+    }
+    coefficients
+}
+
+fn add(a: &[f64], b: &[f64], sign: f64) -> Vec<f64> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0.0) + sign * b.get(i).unwrap_or(&0.0))
+        .collect()
+}
+
+fn multiply(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taylor::{taylor, taylor_coefficients};
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn product_expands_to_standard_form() {
+        let f = (X + 1.0) * (X - 1.0);
+        assert_eq!(f.to_standard_polynomial().unwrap().to_string(), "x ^ 2 - 1");
+        assert_eq!((3.0 * X - X.powf(3.0) + 2.0).to_standard_polynomial().unwrap().to_string(), "-x ^ 3 + 3 * x + 2");
+        assert_eq!(X.sin().to_standard_polynomial(), None);
+    }
+
+    #[test]
+    fn from_roots_has_the_given_zeros() {
+        let f = Function::from_roots(&[1.0, -2.0], 1.0);
+        for x in [-3.0, 0.0, 0.5, 4.0] {
+            assert_eq!(f.eval(x), (x - 1.0) * (x + 2.0));
+        }
+        assert_eq!(f.eval(1.0), 0.0);
+        assert_eq!(f.eval(-2.0), 0.0);
+    }
+
+    #[test]
+    fn companion_matrix_finds_real_roots() {
+        let roots = (X.powf(2.0) - 1.0).polynomial_roots().unwrap();
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] + 1.0).abs() < 1e-12 && (roots[1] - 1.0).abs() < 1e-12, "{roots:?}");
+        assert_eq!((X.powf(2.0) + 1.0).polynomial_roots(), Some(vec![]));
+        assert_eq!(X.sin().polynomial_roots(), None);
+    }
+
+    #[test]
+    fn zero_polynomial_has_no_root_list() {
+        assert_eq!(Function::from(0.0).polynomial_roots(), None);
+        assert_eq!((X * (X + 1.0) - X.powf(2.0) - X).polynomial_roots(), None);
+        assert_eq!(Function::from(3.0).polynomial_roots(), Some(vec![]));
+    }
+
+    #[test]
+    fn perfect_squares_factor() {
+        let f = X.powf(2.0) + 2.0 * X + 1.0;
+        let factored = f.factor_perfect_square();
+        assert_eq!(factored.to_string(), "(x + 1) ^ 2");
+        let g = X.powf(2.0) + 3.0 * X + 1.0;
+        assert_eq!(g.factor_perfect_square(), g);
+        for x in [-2.5, -1.0, 0.0, 3.0] {
+            assert!((factored.eval(x) - f.eval(x)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn monic_form_divides_by_the_leading_coefficient() {
+        let f = 2.0 * X.powf(2.0) + 4.0 * X + 2.0;
+        let monic = f.to_monic().unwrap();
+        assert_eq!(monic.to_string(), "x ^ 2 + 2 * x + 1");
+        for x in [-3.0, 0.5, 2.0] {
+            assert!((2.0 * monic.eval(x) - f.eval(x)).abs() < 1e-12);
+        }
+        assert_eq!(X.exp().to_monic(), None);
+        assert_eq!(Function::from(0.0).to_monic(), None);
+    }
+
+    #[test]
+    fn taylor_polynomials_round_trip_to_their_coefficients() {
+        for (f, center) in [(X.exp(), 0.5), (X.cos(), 0.0), (X.sin() * X, 1.0)] {
+            let coefficients = taylor_coefficients(4, center, &f);
+            let recovered = taylor(4, center, &f).as_polynomial(center).unwrap();
+            assert_eq!(recovered.len(), coefficients.len());
+            for (n, (c, expected)) in recovered.iter().zip(&coefficients).enumerate() {
+                assert!((c - expected).abs() < 1e-12, "{f} coefficient {n}: {c} vs {expected}");
+            }
+        }
+        assert_eq!(X.sin().as_polynomial(0.0), None);
+    }
+}
@@ -0,0 +1,71 @@
+
+use rand::Rng;
+
+use crate::Function;
+
+const MAX_CONSTANT: f64 = 5.0;
+
+// ln, division and variable powers get a g^2 + 1 operand, exponents are squashed into [-2, 2] and tan's
+// argument into [-1, 1], so a generated function has no poles and is real on the whole line. Depths
+// up to about 4 stay finite on [-1, 1]; deeper trees can still overflow through repeated powers.
+pub fn random_function(depth: usize, rng: &mut impl Rng) -> Function {
+    if depth == 0 || rng.gen_bool(0.25) {
+        return random_leaf(rng);
+    }
+    let a = random_function(depth - 1, rng);
+    match rng.gen_range(0..12) {
+        0 => a + random_function(depth - 1, rng),
+        1 => a - random_function(depth - 1, rng),
+        2 => a * random_function(depth - 1, rng),
+        3 => a / positive(random_function(depth - 1, rng)),
+        4 => a.powf(rng.gen_range(1..4) as f64),
+        5 => Function::from(random_constant(rng, 0.5)).pow(&bounded(a)),
+        6 => positive(a).pow(&bounded(random_function(depth - 1, rng))),
+        7 => bounded(a).exp(),
+        8 => positive(a).ln(),
+        9 => a.sin(),
+        10 => a.cos(),
+        _ => a.sin().tan(),
+    }
+}
+
+fn random_leaf(rng: &mut impl Rng) -> Function {
+    if rng.gen_bool(0.5) {
+        Function::X
+    } else {
+        Function::from(random_constant(rng, -MAX_CONSTANT))
+    }
+}
+
+// rounded to one decimal place so generated functions print readably
+fn random_constant(rng: &mut impl Rng, min: f64) -> f64 {
+    (rng.gen_range(min..MAX_CONSTANT) * 10.0).round() / 10.0
+}
+
+fn positive(f: Function) -> Function {
+    f.powf(2.0) + 1.0
+}
+
+fn bounded(f: Function) -> Function {
+    2.0 * f.sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample::grid;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generated_functions_are_finite_and_differentiable() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        for _ in 0..500 {
+            let f = random_function(4, &mut rng);
+            let derivative = f.prime();
+            for x in grid(-1.0, 1.0, 21) {
+                assert!(f.eval(x).is_finite(), "{f} at {x}");
+                let _ = derivative.eval(x);
+            }
+        }
+    }
+}
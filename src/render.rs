@@ -0,0 +1,244 @@
+use crate::Function;
+
+impl Function {
+    pub fn to_ascii_art(&self) -> String {
+        let block = Block::of(self);
+        block.lines.iter().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n")
+    }
+
+    pub fn to_latex(&self) -> String {
+        match self {
+            Self::Constant(a) if *a == std::f64::consts::PI => "\\pi".to_owned(),
+            Self::Constant(a) if *a == std::f64::consts::E => "e".to_owned(),
+            Self::Constant(a) => format!("{}", a),
+            Self::X => "x".to_owned(),
+            Self::Add(a, b) => format!("{} + {}", a.to_latex(), latex_operand(b, 1)),
+            Self::Subtract(a, b) => format!("{} - {}", a.to_latex(), latex_operand(b, 2)),
+            Self::Multiply(a, b) if **a == Self::Constant(-1.0) => format!("-{}", latex_operand(b, 3)),
+            Self::Multiply(a, b) => {
+                let left = if let Self::Constant(_) = **a { a.to_latex() } else { latex_operand(a, 2) };
+                format!("{} \\cdot {}", left, latex_operand(b, 2))
+            }
+            Self::Divide(a, b) => format!("\\frac{{{}}}{{{}}}", a.to_latex(), b.to_latex()),
+            Self::Powi(a, b) if *b == 1.0 / 3.0 => format!("\\sqrt[3]{{{}}}", a.to_latex()),
+            Self::Powi(a, b) => format!("{}^{{{}}}", latex_operand(a, 4), b),
+            Self::Powa(a, b) => format!("{}^{{{}}}", latex_operand(&Self::Constant(*a), 4), b.to_latex()),
+            Self::Pow(a, b) => format!("{}^{{{}}}", latex_operand(a, 4), b.to_latex()),
+            Self::Sqrt(a) => format!("\\sqrt{{{}}}", a.to_latex()),
+            Self::Exp(a) => format!("e^{{{}}}", a.to_latex()),
+            Self::Ln(a) => latex_call("\\ln", a),
+            Self::Sin(a) => latex_call("\\sin", a),
+            Self::Cos(a) => latex_call("\\cos", a),
+            Self::Tan(a) => latex_call("\\tan", a),
+            Self::Asin(a) => latex_call("\\arcsin", a),
+            Self::Acos(a) => latex_call("\\arccos", a),
+            Self::Atan(a) => latex_call("\\arctan", a),
+            Self::Sinh(a) => latex_call("\\sinh", a),
+            Self::Cosh(a) => latex_call("\\cosh", a),
+            Self::Tanh(a) => latex_call("\\tanh", a),
+            Self::Abs(a) => format!("\\left|{}\\right|", a.to_latex()),
+            Self::Erf(a) => latex_call("\\operatorname{erf}", a),
+        }
+    }
+}
+
+// negative constants and negations are wrapped too, so x - -2 comes out as x - (-2)
+fn latex_operand(f: &Function, min_precedence: u8) -> String {
+    match f {
+        Function::Constant(a) if *a < 0.0 => format!("\\left({}\\right)", a),
+        Function::Multiply(a, _) if **a == Function::Constant(-1.0) => format!("\\left({}\\right)", f.to_latex()),
+        _ if f.precedence() < min_precedence => format!("\\left({}\\right)", f.to_latex()),
+        _ => f.to_latex(),
+    }
+}
+
+fn latex_call(name: &str, argument: &Function) -> String {
+    format!("{}\\left({}\\right)", name, argument.to_latex())
+}
+
+struct Block {
+    lines: Vec<String>,
+    baseline: usize,
+}
+
+impl Block {
+    fn of(f: &Function) -> Self {
+        match f {
+            Function::Constant(a) if *a == std::f64::consts::PI => Self::text("π"),
+            Function::Constant(a) if *a == std::f64::consts::E => Self::text("e"),
+            Function::Constant(a) => Self::text(&format!("{}", a)),
+            Function::X => Self::text("x"),
+            Function::Add(a, b) => Self::infix(Self::operand(a, false), " + ", Self::operand(b, false)),
+            Function::Subtract(a, b) => Self::infix(Self::operand(a, false), " - ", Self::operand(b, true)),
+            Function::Multiply(a, b) if **a == Function::Constant(-1.0) => Self::negated(b),
+            Function::Multiply(a, b) => Self::infix(Self::operand(a, true), " * ", Self::operand(b, true)),
+            Function::Divide(a, b) => Self::fraction(Self::of(a), Self::of(b)),
+            Function::Powi(a, b) => Self::raised(Self::base(a), Self::text(&format!("{}", b))),
+            Function::Powa(a, b) => Self::raised(Self::base(&Function::Constant(*a)), Self::of(b)),
+            Function::Pow(a, b) => Self::raised(Self::base(a), Self::of(b)),
+            Function::Sqrt(a) => Self::call("sqrt", a),
+            Function::Exp(a) => Self::raised(Self::text("e"), Self::of(a)),
+            Function::Ln(a) => Self::call("ln", a),
+            Function::Sin(a) => Self::call("sin", a),
+            Function::Cos(a) => Self::call("cos", a),
+            Function::Tan(a) => Self::call("tan", a),
+            Function::Asin(a) => Self::call("asin", a),
+            Function::Acos(a) => Self::call("acos", a),
+            Function::Atan(a) => Self::call("atan", a),
+            Function::Sinh(a) => Self::call("sinh", a),
+            Function::Cosh(a) => Self::call("cosh", a),
+            Function::Tanh(a) => Self::call("tanh", a),
+            Function::Abs(a) => Self::of(a).bars(),
+            Function::Erf(a) => Self::call("erf", a),
+        }
+    }
+
+    fn text(s: &str) -> Self {
+        Self { lines: vec![s.to_owned()], baseline: 0 }
+    }
+
+    fn width(&self) -> usize {
+        self.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0)
+    }
+
+    fn operand(f: &Function, tight: bool) -> Self {
+        match f {
+            Function::Add(_, _) | Function::Subtract(_, _) if tight => Self::of(f).parenthesized(),
+            _ => Self::of(f),
+        }
+    }
+
+    // -(x * y) keeps its parentheses like Display, and a tall operand gets a space so the minus doesn't
+    // run into a fraction bar
+    fn negated(f: &Function) -> Self {
+        let operand = match f {
+            Function::Multiply(_, _) => Self::of(f).parenthesized(),
+            _ => Self::operand(f, true),
+        };
+        let minus = if operand.lines.len() > 1 { "- " } else { "-" };
+        Self::beside(vec![Self::text(minus), operand])
+    }
+
+    fn base(f: &Function) -> Self {
+        match f {
+            Function::Constant(a) if *a >= 0.0 => Self::of(f),
+            Function::X | Function::Sqrt(_) | Function::Ln(_) | Function::Sin(_) | Function::Cos(_) | Function::Tan(_)
+                | Function::Asin(_) | Function::Acos(_) | Function::Atan(_)
+                | Function::Sinh(_) | Function::Cosh(_) | Function::Tanh(_) | Function::Abs(_) | Function::Erf(_) => Self::of(f),
+            _ => Self::of(f).parenthesized(),
+        }
+    }
+
+    fn call(name: &str, argument: &Function) -> Self {
+        Self::beside(vec![Self::text(name), Self::of(argument).parenthesized()])
+    }
+
+    fn infix(left: Self, operator: &str, right: Self) -> Self {
+        Self::beside(vec![left, Self::text(operator), right])
+    }
+
+    fn beside(blocks: Vec<Self>) -> Self {
+        let above = blocks.iter().map(|b| b.baseline).max().unwrap_or(0);
+        let below = blocks.iter().map(|b| b.lines.len() - b.baseline - 1).max().unwrap_or(0);
+        let mut lines = vec![String::new(); above + below + 1];
+        for block in &blocks {
+            let width = block.width();
+            let offset = above - block.baseline;
+            for (row, line) in lines.iter_mut().enumerate() {
+                let cell = row.checked_sub(offset).and_then(|r| block.lines.get(r)).map_or("", String::as_str);
+                line.push_str(&format!("{:<width$}", cell, width = width));
+            }
+        }
+        Self { lines, baseline: above }
+    }
+
+    fn fraction(numerator: Self, denominator: Self) -> Self {
+        let width = numerator.width().max(denominator.width()) + 2;
+        let center = |block: &Self| -> Vec<String> {
+            block.lines.iter().map(|line| format!("{:^width$}", line, width = width)).collect()
+        };
+        let mut lines = center(&numerator);
+        let baseline = lines.len();
+        lines.push("-".repeat(width));
+        lines.extend(center(&denominator));
+        Self { lines, baseline }
+    }
+
+    fn raised(base: Self, exponent: Self) -> Self {
+        let (base_width, exponent_width) = (base.width(), exponent.width());
+        let mut lines: Vec<String> = exponent.lines.iter()
+            .map(|line| format!("{}{:<width$}", " ".repeat(base_width), line, width = exponent_width))
+            .collect();
+        let baseline = lines.len() + base.baseline;
+        lines.extend(base.lines.iter().map(|line| format!("{:<width$}", line, width = base_width + exponent_width)));
+        Self { lines, baseline }
+    }
+
+    fn bars(self) -> Self {
+        let lines = self.lines.iter().map(|line| format!("|{:<width$}|", line, width = self.width())).collect();
+        Self { lines, baseline: self.baseline }
+    }
+
+    fn parenthesized(self) -> Self {
+        let height = self.lines.len();
+        let (open, close) = if height == 1 { ("(", ")") } else { ("|", "|") };
+        let lines = self.lines.iter().enumerate().map(|(row, line)| {
+            let (open, close) = match row {
+                _ if height == 1 => (open, close),
+                0 => ("/", "\\"),
+                r if r == height - 1 => ("\\", "/"),
+                _ => (open, close),
+            };
+            format!("{}{:<width$}{}", open, line, close, width = self.width())
+        }).collect();
+        Self { lines, baseline: self.baseline }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn ascii_fraction_has_a_bar() {
+        assert_eq!((&X / 2.0).to_ascii_art(), " x\n---\n 2");
+    }
+
+    #[test]
+    fn ascii_exponent_is_raised() {
+        assert_eq!(X.powf(2.0).to_ascii_art(), " 2\nx");
+        assert_eq!((X.powf(2.0) / (X + 1.0)).to_ascii_art(), "   2\n  x\n-------\n x + 1");
+    }
+
+    #[test]
+    fn latex_snapshots() {
+        assert_eq!((X.sin() / (X + 1.0)).to_latex(), r"\frac{\sin\left(x\right)}{x + 1}");
+        assert_eq!((X + 1.0).powf(2.0).to_latex(), r"\left(x + 1\right)^{2}");
+        assert_eq!((2.0 * X.cos()).to_latex(), r"2 \cdot \cos\left(x\right)");
+        assert_eq!((X * X.ln()).exp().to_latex(), r"e^{x \cdot \ln\left(x\right)}");
+        assert_eq!(Function::from(2.0).pow(&(X + 1.0)).to_latex(), "2^{x + 1}");
+        assert_eq!(((X - 1.0) * (X + 2.0)).to_latex(), r"\left(x - 1\right) \cdot \left(x + 2\right)");
+        assert_eq!((X.sqrt() - X.tan()).to_latex(), r"\sqrt{x} - \tan\left(x\right)");
+        assert_eq!((X - (X - 1.0)).to_latex(), r"x - \left(x - 1\right)");
+    }
+
+    #[test]
+    fn negation_renders_as_a_leading_minus() {
+        assert_eq!((-1.0 * X).to_latex(), "-x");
+        assert_eq!((-1.0 * (X + 1.0)).to_latex(), "-\\left(x + 1\\right)");
+        assert_eq!((X - -1.0 * X.sin()).to_latex(), "x - \\left(-\\sin\\left(x\\right)\\right)");
+        assert_eq!((-1.0 * X).to_ascii_art(), "-x");
+        assert_eq!((-1.0 * (X * X.sin())).to_ascii_art(), "-(x * sin(x))");
+        assert_eq!((-1.0 * (X / 2.0)).to_ascii_art(), "   x\n- ---\n   2");
+    }
+
+    #[test]
+    fn named_constants_render_symbolically() {
+        assert_eq!((X.sin() / Function::pi()).to_latex(), "\\frac{\\sin\\left(x\\right)}{\\pi}");
+        assert_eq!((X.sin() / Function::pi()).to_ascii_art(), " sin(x)\n--------\n   π");
+        assert_eq!(Function::pi().pow(&X).to_ascii_art(), " x\nπ");
+        assert_eq!((Function::e() - X).to_ascii_art(), "e - x");
+    }
+}
@@ -0,0 +1,213 @@
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use crate::Function;
+use crate::math;
+
+// A program is read left to right against a stack. PushConstant and PushX push one value;
+// Binary pops the right operand, then the left, and pushes the result; Unary replaces the top value.
+// Display writes each token the way a calculator keypad would: numbers, x, + - * / ^, and function names.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RpnToken {
+    PushConstant(f64),
+    PushX,
+    Binary(BinaryOp),
+    Unary(UnaryOp),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Pow,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum UnaryOp {
+    Sqrt,
+    Cbrt,
+    Exp,
+    Ln,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Abs,
+    Erf,
+}
+
+impl fmt::Display for RpnToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let repr = match self {
+            Self::PushConstant(a) => return write!(f, "{}", a),
+            Self::PushX => "x",
+            Self::Binary(BinaryOp::Add) => "+",
+            Self::Binary(BinaryOp::Subtract) => "-",
+            Self::Binary(BinaryOp::Multiply) => "*",
+            Self::Binary(BinaryOp::Divide) => "/",
+            Self::Binary(BinaryOp::Pow) => "^",
+            Self::Unary(UnaryOp::Sqrt) => "sqrt",
+            Self::Unary(UnaryOp::Cbrt) => "cbrt",
+            Self::Unary(UnaryOp::Exp) => "exp",
+            Self::Unary(UnaryOp::Ln) => "ln",
+            Self::Unary(UnaryOp::Sin) => "sin",
+            Self::Unary(UnaryOp::Cos) => "cos",
+            Self::Unary(UnaryOp::Tan) => "tan",
+            Self::Unary(UnaryOp::Asin) => "asin",
+            Self::Unary(UnaryOp::Acos) => "acos",
+            Self::Unary(UnaryOp::Atan) => "atan",
+            Self::Unary(UnaryOp::Sinh) => "sinh",
+            Self::Unary(UnaryOp::Cosh) => "cosh",
+            Self::Unary(UnaryOp::Tanh) => "tanh",
+            Self::Unary(UnaryOp::Abs) => "abs",
+            Self::Unary(UnaryOp::Erf) => "erf",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl Function {
+    pub fn to_rpn_program(&self) -> Vec<RpnToken> {
+        let mut program = vec![];
+        self.push_rpn(&mut program);
+        program
+    }
+
+    fn push_rpn(&self, program: &mut Vec<RpnToken>) {
+        let (children, token): (Vec<&Function>, RpnToken) = match self {
+            Self::Constant(a) => (vec![], RpnToken::PushConstant(*a)),
+            Self::X => (vec![], RpnToken::PushX),
+            Self::Add(a, b) => (vec![a, b], RpnToken::Binary(BinaryOp::Add)),
+            Self::Subtract(a, b) => (vec![a, b], RpnToken::Binary(BinaryOp::Subtract)),
+            Self::Multiply(a, b) => (vec![a, b], RpnToken::Binary(BinaryOp::Multiply)),
+            Self::Divide(a, b) => (vec![a, b], RpnToken::Binary(BinaryOp::Divide)),
+            // the cube root gets its own key so negative bases keep their sign like in eval
+            Self::Powi(a, b) if *b == 1.0 / 3.0 => (vec![a], RpnToken::Unary(UnaryOp::Cbrt)),
+            Self::Powi(a, b) => {
+                a.push_rpn(program);
+                program.extend([RpnToken::PushConstant(*b), RpnToken::Binary(BinaryOp::Pow)]);
+                return;
+            }
+            Self::Powa(a, b) => {
+                program.push(RpnToken::PushConstant(*a));
+                b.push_rpn(program);
+                program.push(RpnToken::Binary(BinaryOp::Pow));
+                return;
+            }
+            Self::Pow(a, b) => (vec![a, b], RpnToken::Binary(BinaryOp::Pow)),
+            Self::Sqrt(a) => (vec![a], RpnToken::Unary(UnaryOp::Sqrt)),
+            Self::Exp(a) => (vec![a], RpnToken::Unary(UnaryOp::Exp)),
+            Self::Ln(a) => (vec![a], RpnToken::Unary(UnaryOp::Ln)),
+            Self::Sin(a) => (vec![a], RpnToken::Unary(UnaryOp::Sin)),
+            Self::Cos(a) => (vec![a], RpnToken::Unary(UnaryOp::Cos)),
+            Self::Tan(a) => (vec![a], RpnToken::Unary(UnaryOp::Tan)),
+            Self::Asin(a) => (vec![a], RpnToken::Unary(UnaryOp::Asin)),
+            Self::Acos(a) => (vec![a], RpnToken::Unary(UnaryOp::Acos)),
+            Self::Atan(a) => (vec![a], RpnToken::Unary(UnaryOp::Atan)),
+            Self::Sinh(a) => (vec![a], RpnToken::Unary(UnaryOp::Sinh)),
+            Self::Cosh(a) => (vec![a], RpnToken::Unary(UnaryOp::Cosh)),
+            Self::Tanh(a) => (vec![a], RpnToken::Unary(UnaryOp::Tanh)),
+            Self::Abs(a) => (vec![a], RpnToken::Unary(UnaryOp::Abs)),
+            Self::Erf(a) => (vec![a], RpnToken::Unary(UnaryOp::Erf)),
+        };
+        for child in children {
+            child.push_rpn(program);
+        }
+        program.push(token);
+    }
+}
+
+// None if the program underflows the stack or leaves anything other than a single value
+pub fn eval_rpn_program(program: &[RpnToken], x: f64) -> Option<f64> {
+    let mut stack: Vec<f64> = vec![];
+    for token in program {
+        let value = match token {
+            RpnToken::PushConstant(a) => *a,
+            RpnToken::PushX => x,
+            RpnToken::Binary(op) => {
+                let (b, a) = (stack.pop()?, stack.pop()?);
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Subtract => a - b,
+                    BinaryOp::Multiply => a * b,
+                    BinaryOp::Divide => a / b,
+                    BinaryOp::Pow => math::powf(a, b),
+                }
+            }
+            RpnToken::Unary(op) => {
+                let a = stack.pop()?;
+                match op {
+                    UnaryOp::Sqrt => math::sqrt(a),
+                    UnaryOp::Cbrt => math::cbrt(a),
+                    UnaryOp::Exp => math::exp(a),
+                    UnaryOp::Ln => math::ln(a),
+                    UnaryOp::Sin => math::sin(a),
+                    UnaryOp::Cos => math::cos(a),
+                    UnaryOp::Tan => math::tan(a),
+                    UnaryOp::Asin => math::asin(a),
+                    UnaryOp::Acos => math::acos(a),
+                    UnaryOp::Atan => math::atan(a),
+                    UnaryOp::Sinh => math::sinh(a),
+                    UnaryOp::Cosh => math::cosh(a),
+                    UnaryOp::Tanh => math::tanh(a),
+                    UnaryOp::Abs => a.abs(),
+                    UnaryOp::Erf => math::erf(a),
+                }
+            }
+        };
+        stack.push(value);
+    }
+    match stack[..] {
+        [value] => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn program_is_postfix() {
+        let program = (X.powf(2.0) + X.sin()).to_rpn_program();
+        let expected = [
+            RpnToken::PushX,
+            RpnToken::PushConstant(2.0),
+            RpnToken::Binary(BinaryOp::Pow),
+            RpnToken::PushX,
+            RpnToken::Unary(UnaryOp::Sin),
+            RpnToken::Binary(BinaryOp::Add),
+        ];
+        assert_eq!(program, expected);
+        assert_eq!(eval_rpn_program(&program[1..], 1.0), None);
+    }
+
+    #[test]
+    fn rpn_program_reproduces_eval() {
+        let functions = [
+            X.cos().powf(3.0) - 2.0 * X / (X.powf(2.0) + 1.0),
+            Function::from(2.0).pow(&X.sinh()) + X.abs().sqrt(),
+            X.powf(1.0 / 3.0) * X.atan().erf(),
+            X.pow(&X) - X.tan().exp(),
+        ];
+        for f in functions {
+            let program = f.to_rpn_program();
+            for k in 0..=40 {
+                let x = -2.0 + 0.1 * k as f64;
+                let (expected, value) = (f.eval(x), eval_rpn_program(&program, x).unwrap());
+                assert!(value == expected || (value - expected).abs() < 1e-12 * expected.abs()
+                    || (value.is_nan() && expected.is_nan()), "{f} at {x}");
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+
+use crate::Function;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NonFinite {
+    Skip,
+    Empty,
+}
+
+pub fn grid(a: f64, b: f64, n: usize) -> Vec<f64> {
+    match n {
+        0 => vec![],
+        1 => vec![a],
+        _ => (0..n).map(|i| a + (b - a) * i as f64 / (n - 1) as f64).collect(),
+    }
+}
+
+pub fn sample(f: &Function, a: f64, b: f64, n: usize) -> Vec<(f64, f64)> {
+    grid(a, b, n).into_iter().map(|x| (x, f.eval(x))).collect()
+}
+
+pub fn to_csv(f: &Function, a: f64, b: f64, n: usize) -> String {
+    to_csv_with(f, a, b, n, NonFinite::Skip)
+}
+
+pub fn to_csv_with(f: &Function, a: f64, b: f64, n: usize, non_finite: NonFinite) -> String {
+    let mut csv = "x,y\n".to_owned();
+    for (x, y) in sample(f, a, b, n) {
+        match (y.is_finite(), non_finite) {
+            (true, _) => csv.push_str(&format!("{},{}\n", x, y)),
+            (false, NonFinite::Empty) => csv.push_str(&format!("{},\n", x)),
+            (false, NonFinite::Skip) => {}
+        }
+    }
+    csv
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SampleError {
+    NonPositiveBound,
+}
+
+pub fn sample_log(f: &Function, a: f64, b: f64, n: usize) -> Result<Vec<(f64, f64)>, SampleError> {
+    if a <= 0.0 || b <= 0.0 || a.is_nan() || b.is_nan() {
+        return Err(SampleError::NonPositiveBound);
+    }
+    let xs = grid(a.ln(), b.ln(), n).into_iter().enumerate().map(|(i, t)| match i {
+        0 => a,
+        _ if i == n - 1 => b,
+        _ => t.exp(),
+    });
+    Ok(xs.map(|x| (x, f.eval(x))).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn csv_has_header_and_one_row_per_point() {
+        let csv = to_csv(&X.powf(2.0), 0.0, 2.0, 5);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "x,y");
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "0.5,0.25");
+        assert_eq!(lines[5], "2,4");
+    }
+
+    #[test]
+    fn csv_skips_or_blanks_non_finite_values() {
+        let f = 1.0 / X;
+        assert_eq!(to_csv(&f, -1.0, 1.0, 3), "x,y\n-1,-1\n1,1\n");
+        assert_eq!(to_csv_with(&f, -1.0, 1.0, 3, NonFinite::Empty), "x,y\n-1,-1\n0,\n1,1\n");
+    }
+
+    #[test]
+    fn log_grid_is_geometric_and_keeps_endpoints() {
+        let points = sample_log(&X, 1.0, 1000.0, 4).unwrap();
+        assert_eq!(points.first().unwrap().0, 1.0);
+        assert_eq!(points.last().unwrap().0, 1000.0);
+        for pair in points.windows(2) {
+            assert!((pair[1].0 / pair[0].0 - 10.0).abs() < 1e-12, "{points:?}");
+        }
+        assert_eq!(sample_log(&X, 0.0, 1.0, 4), Err(SampleError::NonPositiveBound));
+    }
+}
@@ -0,0 +1,396 @@
+use std::hash::{Hash, Hasher};
+
+use crate::function::Function;
+
+impl Function {
+    pub fn simplify(&self) -> Function {
+        let simplified = self.map_children(Function::simplify);
+        match &simplified {
+            Self::Add(_, _) | Self::Subtract(_, _) => collect_terms(&simplified),
+            Self::Multiply(_, _) | Self::Divide(_, _) => collect_factors(&simplified),
+            Self::Exp(f) | Self::Ln(f) | Self::Sin(f) | Self::Cos(f) | Self::Tan(f) => {
+                cancel_inverse(&simplified, f).unwrap_or(simplified)
+            }
+            Self::Powi(_, _) | Self::Pow(_, _) => merge_tower(&simplified).unwrap_or(simplified),
+            _ => simplified,
+        }
+    }
+
+    // f^c = exp(c ln f) only holds for f > 0, so integer powers of negative values become NaN
+    pub fn to_exp_log_form(&self) -> Function {
+        let rewritten = self.map_children(Function::to_exp_log_form);
+        match &rewritten {
+            Self::Powi(f, c) => (*c * f.ln()).exp(),
+            Self::Sqrt(f) => (0.5 * f.ln()).exp(),
+            Self::Powa(a, f) => (Function::clone(f) * Function::from(*a).ln()).exp(),
+            Self::Pow(f, g) => (Function::clone(g) * f.ln()).exp(),
+            _ => rewritten,
+        }
+    }
+
+    // canonicalize sorts + and * operands, so operand order doesn't matter
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = Fnv::default();
+        self.simplify().canonicalize().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// FNV-1a with integers fed in little-endian, so structural_hash gives the same key on every platform
+// and Rust release, which DefaultHasher doesn't promise
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    // the signed writes default to these, so discriminants and exponents are covered too
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+}
+
+// exp(ln(f)) = f only holds where f > 0, and sin(asin(f)) = f and cos(acos(f)) = f only where |f| <= 1;
+// those rewrites widen the domain of the result. ln(exp(f)) = f and tan(atan(f)) = f hold for every real f.
+// asin(sin(f)) and friends are left alone, since they only equal f on one branch.
+fn cancel_inverse(outer: &Function, inner: &Function) -> Option<Function> {
+    match (outer, inner) {
+        (Function::Exp(_), Function::Ln(f)) | (Function::Ln(_), Function::Exp(f))
+            | (Function::Sin(_), Function::Asin(f)) | (Function::Cos(_), Function::Acos(f))
+            | (Function::Tan(_), Function::Atan(f)) => Some(Function::clone(f)),
+        _ => None,
+    }
+}
+
+// (a^g)^h = a^(g*h) only for a > 0, where a^g is positive: a negative base has no real a^g for most g,
+// and a^(g*h) could still be defined where the tower isn't. (f^b)^c = f^(b*c) needs integer c, since
+// (x^2)^0.5 is |x|, and must not drop a fractional b's f >= 0 domain, so (x^0.5)^2 stays rather than becoming x
+fn merge_tower(power: &Function) -> Option<Function> {
+    let (base, outer) = match power {
+        Function::Powi(f, c) => (f, Function::Constant(*c)),
+        Function::Pow(f, g) => (f, Function::clone(g)),
+        _ => return None,
+    };
+    match (&**base, outer) {
+        (Function::Powa(a, g), outer) if *a > 0.0 => Some(Function::Constant(*a).pow(&(outer * Function::clone(g)).simplify())),
+        (Function::Exp(g), outer) => Some((outer * Function::clone(g)).simplify().exp()),
+        (Function::Powi(f, b), Function::Constant(c)) if c.fract() == 0.0 && (b.fract() == 0.0 || (b * c).fract() != 0.0) => {
+            Some(raise(f, b * c))
+        }
+        (Function::Sqrt(f), Function::Constant(c)) if c.fract() == 0.0 && (c / 2.0).fract() != 0.0 => Some(raise(f, c / 2.0)),
+        _ => None,
+    }
+}
+
+fn raise(base: &Function, exponent: f64) -> Function {
+    let power = base.powf(exponent);
+    merge_tower(&power).unwrap_or(power)
+}
+
+fn collect(entries: &mut Vec<(Function, f64)>, f: &Function, amount: f64) {
+    match entries.iter_mut().find(|(g, _)| same_up_to_order(g, f)) {
+        Some((_, total)) => *total += amount,
+        None => entries.push((f.clone(), amount)),
+    }
+}
+
+// products match regardless of factor order, so g*h and -(h*g) still cancel
+fn same_up_to_order(a: &Function, b: &Function) -> bool {
+    if a == b {
+        return true;
+    }
+    if !matches!((a, b), (Function::Multiply(_, _), Function::Multiply(_, _))) {
+        return false;
+    }
+    let (mut left, mut right) = (vec![], vec![]);
+    a.flatten_into(a, &mut left);
+    b.flatten_into(b, &mut right);
+    if left.len() != right.len() {
+        return false;
+    }
+    for factor in left {
+        match right.iter().position(|g| *g == factor) {
+            Some(i) => {
+                right.swap_remove(i);
+            }
+            None => return false,
+        }
+    }
+    true
+}
+
+fn push_terms(f: &Function, sign: f64, terms: &mut Vec<(Function, f64)>, constant: &mut f64) {
+    match f {
+        Function::Add(a, b) => {
+            push_terms(a, sign, terms, constant);
+            push_terms(b, sign, terms, constant);
+        }
+        Function::Subtract(a, b) => {
+            push_terms(a, sign, terms, constant);
+            push_terms(b, -sign, terms, constant);
+        }
+        Function::Constant(a) => *constant += sign * a,
+        // c / g is c times the term 1 / g, so 2 / x^2 and 6 / x^2 collect like 2x and 6x do
+        Function::Divide(a, b) => match (&**a, &**b) {
+            (_, Function::Constant(d)) => push_terms(a, sign / d, terms, constant),
+            (Function::Constant(c), g) => collect(terms, &(1.0 / g), sign * c),
+            _ => collect(terms, f, sign),
+        },
+        Function::Multiply(a, b) => match (&**a, &**b) {
+            (Function::Constant(c), g) | (g, Function::Constant(c)) => push_terms(g, sign * c, terms, constant),
+            _ => collect(terms, f, sign),
+        },
+        _ => collect(terms, f, sign),
+    }
+}
+
+fn collect_terms(sum: &Function) -> Function {
+    let (mut terms, mut constant) = (vec![], 0.0);
+    push_terms(sum, 1.0, &mut terms, &mut constant);
+    let mut result = Function::from(0.0);
+    for (term, coefficient) in terms.into_iter().chain([(Function::from(1.0), constant)]) {
+        if coefficient == 0.0 {
+            continue;
+        }
+        result = if coefficient < 0.0 && result != Function::Constant(0.0) {
+            result - scale(-coefficient, term)
+        } else {
+            result + scale(coefficient, term)
+        };
+    }
+    result
+}
+
+// the coefficient is folded into the term's own factors so a second simplify pass has nothing left to merge
+fn scale(coefficient: f64, term: Function) -> Function {
+    if coefficient == 1.0 { term } else { collect_factors(&(coefficient * term)) }
+}
+
+// an integer power of a product is spread over its factors, so (-8 / u)^2 meets 96 / u^2 as 64 / u^2;
+// fractional powers aren't, since (x * x)^0.5 is |x| rather than x
+fn push_factors(f: &Function, exponent: f64, factors: &mut Vec<(Function, f64)>, constants: &mut (f64, f64)) {
+    match f {
+        Function::Multiply(a, b) => {
+            push_factors(a, exponent, factors, constants);
+            push_factors(b, exponent, factors, constants);
+        }
+        Function::Divide(a, b) => {
+            push_factors(a, exponent, factors, constants);
+            push_factors(b, -exponent, factors, constants);
+        }
+        Function::Constant(a) if exponent > 0.0 => constants.0 *= a.powf(exponent),
+        Function::Constant(a) => constants.1 *= a.powf(-exponent),
+        Function::Powi(g, n) if n.fract() == 0.0 && matches!(**g, Function::Multiply(_, _) | Function::Divide(_, _)) => {
+            push_factors(g, exponent * n, factors, constants)
+        }
+        Function::Powi(g, n) => collect(factors, g, exponent * n),
+        Function::Sqrt(g) => collect(factors, g, exponent * 0.5),
+        _ => collect(factors, f, exponent),
+    }
+}
+
+// sin(u)^k / cos(u)^k becomes tan(u)^k; the zeroed cosine is dropped with the other cancelled factors
+fn combine_tangents(factors: &mut [(Function, f64)]) {
+    for i in 0..factors.len() {
+        let (Function::Sin(u), exponent) = &factors[i] else { continue };
+        let (u, exponent) = (Function::clone(u), *exponent);
+        let cosine = factors.iter().position(|(g, e)| *e == -exponent && *g == u.cos());
+        if let Some(j) = cosine {
+            factors[i] = (u.tan(), exponent);
+            factors[j].1 = 0.0;
+        }
+    }
+}
+
+// like factors cancel by adding exponents, so x / x becomes 1 even though it is undefined at x = 0
+fn collect_factors(product: &Function) -> Function {
+    let (mut factors, mut constants) = (vec![], (1.0, 1.0));
+    push_factors(product, 1.0, &mut factors, &mut constants);
+    combine_tangents(&mut factors);
+    let (mut numerator, mut denominator) = (Function::from(1.0), Function::from(1.0));
+    for (base, exponent) in factors {
+        if exponent > 0.0 {
+            numerator = numerator * raise(&base, exponent);
+        } else if exponent < 0.0 {
+            denominator = denominator * raise(&base, -exponent);
+        }
+    }
+    let (top, bottom) = constants;
+    let quotient = top / bottom;
+    let (top, bottom) = if quotient * bottom == top && quotient.fract() == 0.0 { (quotient, 1.0) } else { (top, bottom) };
+    match bottom * denominator {
+        Function::Constant(1.0) => top * numerator,
+        denominator => (top * numerator) / denominator,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn inverse_pairs_cancel() {
+        let pairs = [
+            (X.ln().exp(), 0.1..3.0),
+            (X.exp().ln(), -3.0..3.0),
+            (X.asin().sin(), -1.0..1.0),
+            (X.acos().cos(), -1.0..1.0),
+            (X.atan().tan(), -3.0..3.0),
+            (X.exp().ln().exp().ln(), -3.0..3.0),
+        ];
+        for (f, domain) in pairs {
+            assert_eq!(f.simplify(), X, "{f}");
+            for k in 0..=10 {
+                let x = domain.start + (domain.end - domain.start) * k as f64 / 10.0;
+                assert!((f.eval(x) - x).abs() < 1e-12, "{f} at {x}");
+            }
+        }
+        assert_eq!(X.sin().asin().simplify(), X.sin().asin());
+    }
+
+    #[test]
+    fn like_terms_and_factors_fold_away() {
+        let difference = (2.0 + &X) - &X;
+        let quotient = (&X * 3.0) / &X;
+        assert_eq!(difference.simplify(), Function::from(2.0));
+        // x / x cancels even though it is undefined at 0
+        assert_eq!(quotient.simplify(), Function::from(3.0));
+        for x in [-2.0, 0.5, 7.0] {
+            assert_eq!(difference.eval(x), 2.0);
+            assert_eq!(quotient.eval(x), 3.0);
+        }
+    }
+
+    #[test]
+    fn exact_negations_cancel() {
+        assert_eq!((&X.sin() - &X.sin()).simplify(), Function::from(0.0));
+        assert_eq!((2.0 * &X + (-2.0) * &X).simplify(), Function::from(0.0));
+        assert_eq!((X.sin() * X.cos() - X.cos() * X.sin()).simplify(), Function::from(0.0));
+    }
+
+    #[test]
+    fn reciprocal_factors_become_division() {
+        let f = &X.sin() * (1.0 / &X.cos());
+        assert_eq!(f.simplify(), X.tan());
+        assert_eq!((X.exp() * X.powf(-1.0)).simplify(), X.exp() / X);
+        for x in [-1.0, 0.2, 1.3] {
+            assert!((f.simplify().eval(x) - f.eval(x)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn structural_hash_ignores_operand_order() {
+        assert_eq!((X + 1.0).structural_hash(), (1.0 + X).structural_hash());
+        assert_eq!((X.sin() * X * 2.0).structural_hash(), (2.0 * X * X.sin()).structural_hash());
+        assert_ne!((X + 1.0).structural_hash(), (X + 2.0).structural_hash());
+        assert_ne!(X.sin().structural_hash(), X.cos().structural_hash());
+    }
+
+    #[test]
+    fn structural_hash_is_pinned() {
+        // FNV-1a of the variant index of X as eight little-endian bytes
+        assert_eq!(X.structural_hash(), 0x89cd_3129_1d2a_efa4);
+        assert_eq!((X + 1.0).structural_hash(), 0x942c_4e29_1ed9_a200);
+    }
+
+    #[test]
+    fn power_towers_merge() {
+        let towers = [
+            (Function::from(2.0).pow(&X).powf(3.0), "2 ^ (3 * x)"),
+            (X.exp().powf(2.0), "e ^ (2 * x)"),
+            (X.powf(2.0).powf(3.0), "x ^ 6"),
+            (Function::from(2.0).pow(&X).pow(&X.sin()), "2 ^ (sin(x) * x)"),
+        ];
+        for (tower, expected) in towers {
+            let merged = tower.simplify();
+            assert_eq!(merged.to_string(), expected);
+            for x in [-1.0, 0.5, 1.5] {
+                assert!((merged.eval(x) - tower.eval(x)).abs() < 1e-12 * tower.eval(x).abs().max(1.0));
+            }
+        }
+        // (x^2)^0.5 is |x|, not x
+        assert_ne!(X.powf(2.0).powf(0.5).simplify(), X);
+    }
+
+    #[test]
+    fn power_towers_keep_their_domain() {
+        // each of these is NaN somewhere the merged power wouldn't be
+        let towers = [
+            X.powf(0.5).powf(2.0),
+            X.powf(0.5).powf(4.0),
+            X.powf(1.5).powf(2.0),
+            Function::from(-2.0).pow(&X).powf(2.0),
+        ];
+        for tower in towers {
+            let simplified = tower.simplify();
+            assert!(tower.eval(-0.5).is_nan(), "{tower}");
+            assert!(simplified.eval(-0.5).is_nan(), "{tower} became {simplified}");
+        }
+        // a fractional result keeps f >= 0, so it still merges
+        assert_eq!(X.powf(0.5).powf(3.0).simplify(), X.powf(1.5));
+    }
+
+    #[test]
+    fn powers_rewrite_to_exp_and_ln() {
+        let exponential = Function::from(2.0).pow(&X);
+        let rewritten = exponential.to_exp_log_form();
+        assert_eq!(rewritten, (X * Function::from(2.0).ln()).exp());
+        let tower = X.pow(&X).to_exp_log_form();
+        assert_eq!(tower, (X * X.ln()).exp());
+        for x in [0.5f64, 1.0, 2.5] {
+            assert!((rewritten.eval(x) - 2f64.powf(x)).abs() < 1e-12);
+            assert!((tower.eval(x) - x.powf(x)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn simplify_shrinks_derivatives_and_is_idempotent() {
+        let f = X.cos().powf(2.0);
+        for n in [1, 4] {
+            let raw = f.nth_derivative(n);
+            let simplified = raw.simplify();
+            assert_eq!(simplified.simplify(), simplified);
+            for x in [-1.0, 0.3, 2.0] {
+                assert!((simplified.eval(x) - raw.eval(x)).abs() < 1e-12, "{x}");
+            }
+        }
+        assert!(f.prime().simplify().node_count() < f.prime().node_count());
+        let fourth = f.nth_derivative(4);
+        assert!(fourth.simplify().node_count() * 5 < fourth.node_count());
+        assert_eq!(((X + 0.0) * 1.0 - 0.0).simplify(), X);
+    }
+
+    #[test]
+    fn structural_hash_agrees_for_reordered_roots() {
+        assert_eq!((X.powf(0.5) + X.sin()).structural_hash(), (X.sin() + X.sqrt()).structural_hash());
+        assert_eq!((X.exp() * X.ln() + X).structural_hash(), (X + X.ln() * X.exp()).structural_hash());
+        assert_ne!((X - X.sin()).structural_hash(), (X.sin() - X).structural_hash());
+    }
+}
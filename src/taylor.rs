@@ -1,17 +1,568 @@
+
 use crate::Function;
+use crate::function::DerivativeCache;
+use crate::math::Float;
+
+pub struct TaylorBuilder<T = f64> {
+    center: T,
+    nth_derivative: Function<T>,
+    coefficients: Vec<T>,
+    polynomial: Function<T>,
+    cache: DerivativeCache<T>,
+}
+
+impl<T: Float> TaylorBuilder<T> {
+    pub fn new(center: T, f: &Function<T>) -> Self {
+        Self {
+            center,
+            nth_derivative: f.clone(),
+            coefficients: vec![],
+            polynomial: Function::from(T::zero()),
+            cache: DerivativeCache::default(),
+        }
+    }
+
+    pub fn next_term(&mut self) -> &Function<T> {
+        let n = self.coefficients.len() as u64;
+        let coefficient = self.nth_derivative.eval_shared(self.center) / T::of(factorial(n));
+        let polynomial = std::mem::replace(&mut self.polynomial, Function::from(T::zero()));
+        self.polynomial = add_term(polynomial, coefficient, self.center, n);
+        self.coefficients.push(coefficient);
+        self.nth_derivative = self.cache.prime(&self.nth_derivative);
+        &self.polynomial
+    }
+
+    pub fn order(&self) -> Option<u64> {
+        (self.coefficients.len() as u64).checked_sub(1)
+    }
+
+    pub fn polynomial(&self) -> &Function<T> {
+        &self.polynomial
+    }
+
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefficients
+    }
+
+    pub fn with_order(center: T, f: &Function<T>, order: u64) -> Self {
+        let mut builder = Self::new(center, f);
+        for _ in 0..= order {
+            builder.next_term();
+        }
+        builder
+    }
+}
+
+pub struct Polynomial {
+    center: f64,
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    pub fn eval(&self, x: f64) -> f64 {
+        let offset = x - self.center;
+        self.coefficients.iter().rev().fold(0.0, |acc, &c| acc * offset + c)
+    }
+
+    pub fn center(&self) -> f64 {
+        self.center
+    }
+
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    pub fn to_function(&self) -> Function {
+        self.coefficients.iter().enumerate()
+            .fold(Function::from(0.0), |polynomial, (n, &c)| add_term(polynomial, c, self.center, n as u64))
+    }
+}
+
+pub struct PreparedFunction {
+    derivatives: Vec<Function>,
+}
+
+impl PreparedFunction {
+    pub fn max_order(&self) -> u64 {
+        self.derivatives.len() as u64 - 1
+    }
+
+    // None past max_order, since those derivatives were never prepared
+    pub fn taylor(&self, center: f64, order: u64) -> Option<Function> {
+        if order > self.max_order() {
+            return None;
+        }
+        let mut polynomial = Function::from(0.0);
+        for (n, nth_derivative) in self.derivatives.iter().take(order as usize + 1).enumerate() {
+            let coefficient = nth_derivative.eval_shared(center) / factorial(n as u64);
+            polynomial = add_term(polynomial, coefficient, center, n as u64);
+        }
+        Some(polynomial)
+    }
+}
+
+pub fn prepare_derivatives(f: &Function, max_order: u64) -> PreparedFunction {
+    let mut cache = DerivativeCache::default();
+    let mut derivatives = vec![f.clone()];
+    for _ in 0..max_order {
+        let next = cache.prime(&derivatives[derivatives.len() - 1]);
+        derivatives.push(next);
+    }
+    PreparedFunction { derivatives }
+}
+
+fn term<T: Float>(coefficient: T, center: T, n: u64) -> Function<T> {
+    Function::Constant(coefficient) * (Function::X - Function::Constant(center)).powf(T::of(n as f64))
+}
+
+// negative coefficients are subtracted, as in from_monomials, so the series reads 1 - x ^ 2 rather than 1 + -x ^ 2
+fn add_term<T: Float>(polynomial: Function<T>, coefficient: T, center: T, n: u64) -> Function<T> {
+    if coefficient < T::zero() && polynomial != Function::from(T::zero()) {
+        polynomial - term(-coefficient, center, n)
+    } else {
+        polynomial + term(coefficient, center, n)
+    }
+}
+
+pub fn taylor<T: Float>(order: u64, center: T, f: &Function<T>) -> Function<T> {
+    TaylorBuilder::with_order(center, f, order).polynomial
+}
+
+pub fn taylor_polynomial(order: u64, center: f64, f: &Function) -> Polynomial {
+    Polynomial { center, coefficients: taylor_coefficients(order, center, f) }
+}
+
+pub fn taylor_coefficients(order: u64, center: f64, f: &Function) -> Vec<f64> {
+    TaylorBuilder::with_order(center, f, order).coefficients
+}
+
+pub fn error_by_order(x: f64, center: f64, f: &Function, max_order: u64) -> Vec<f64> {
+    let exact = f.eval(x);
+    let mut builder = TaylorBuilder::new(center, f);
+    (0..= max_order).map(|_| (exact - builder.next_term().eval(x)).abs()).collect()
+}
+
+// the caller bounds the (order + 1)-th derivative on the interval, so f itself is never evaluated
+pub fn taylor_remainder_bound(order: u64, center: f64, x: f64, _f: &Function, derivative_max: f64) -> f64 {
+    derivative_max.abs() * (x - center).abs().powi(order as i32 + 1) / factorial(order + 1)
+}
+
+pub fn taylor_accuracy_table(center: f64, f: &Function, x: f64, max_order: u64) -> Vec<(u64, f64, f64, f64)> {
+    let exact = f.eval(x);
+    let mut builder = TaylorBuilder::new(center, f);
+    (0..= max_order)
+        .map(|order| {
+            let approximation = builder.next_term().eval(x);
+            (order, approximation, exact, ((approximation - exact) / exact).abs())
+        })
+        .collect()
+}
+
+pub fn multiply_series(a: &[f64], b: &[f64], order: usize) -> Vec<f64> {
+    (0..=order)
+        .map(|n| (0..=n).map(|k| a.get(k).unwrap_or(&0.0) * b.get(n - k).unwrap_or(&0.0)).sum())
+        .collect()
+}
+
+pub fn reciprocal_series(center: f64, f: &Function, order: u64) -> Option<Function> {
+    let a = taylor_coefficients(order, center, f);
+    if a[0] == 0.0 {
+        return None;
+    }
+    let mut b: Vec<f64> = vec![1.0 / a[0]];
+    for n in 1..a.len() {
+        let sum: f64 = (1..= n).map(|k| a[k] * b[n - k]).sum();
+        b.push(-sum / a[0]);
+    }
+    Some(b.iter().enumerate().fold(Function::from(0.0), |series, (n, &c)| add_term(series, c, center, n as u64)))
+}
+
+pub fn product_coefficients(order: u64, center: f64, f: &Function, g: &Function) -> Vec<f64> {
+    multiply_series(&taylor_coefficients(order, center, f), &taylor_coefficients(order, center, g), order as usize)
+}
+
+// a float product so 21! and up don't overflow; it stays exact through 22!
+pub fn factorial(n: u64) -> f64 {
+    (2..=n).map(|k| k as f64).product()
+}
+
+pub struct PiecewiseFunction {
+    start: f64,
+    width: f64,
+    pieces: Vec<Function>,
+}
 
-pub fn taylor(order: u64, center: f64, f: &Function) -> Function {
-    let mut polynomial = Function::from(0.0);
-    let mut nth_derivative = f.clone();
-    for n in 0..= order {
-        let coefficient = nth_derivative.eval(center) / factorial(n) as f64;
-        let nth_term = coefficient * (Function::X - center).powf(n as f64);
-        polynomial = polynomial + nth_term;
-        nth_derivative = nth_derivative.prime();
+impl PiecewiseFunction {
+    pub fn eval(&self, x: f64) -> f64 {
+        let index = ((x - self.start) / self.width).floor().clamp(0.0, (self.pieces.len() - 1) as f64);
+        self.pieces[index as usize].eval(x)
     }
-    polynomial
+
+    pub fn pieces(&self) -> &[Function] {
+        &self.pieces
+    }
+}
+
+pub fn piecewise_taylor(f: &Function, a: f64, b: f64, pieces: usize, order: u64) -> PiecewiseFunction {
+    let prepared = prepare_derivatives(f, order);
+    let width = (b - a) / pieces.max(1) as f64;
+    let pieces = (0..pieces.max(1))
+        .map(|i| prepared.taylor(a + (i as f64 + 0.5) * width, order))
+        .collect::<Option<_>>()
+        .expect("derivatives were prepared up to order");
+    PiecewiseFunction { start: a, width, pieces }
+}
+
+pub fn taylor_partial_sums(max_order: u64, center: f64, f: &Function) -> Vec<Function> {
+    let mut builder = TaylorBuilder::new(center, f);
+    (0..= max_order).map(|_| builder.next_term().simplify()).collect()
+}
+
+// outer_coeffs is a Maclaurin series, so this is only the series of f(g(x)) when g(0) = 0
+pub fn compose_series(outer_coeffs: &[f64], inner: &Function, order: u64) -> Vec<f64> {
+    let inner_coeffs = taylor_coefficients(order, 0.0, inner);
+    let order = order as usize;
+    outer_coeffs.iter().take(order + 1).rev().fold(vec![0.0; order + 1], |acc, &a| {
+        let mut next = multiply_series(&acc, &inner_coeffs, order);
+        next[0] += a;
+        next
+    })
+}
+
+// ln Γ(x) = (x - 1/2) ln x - x + ln(2π)/2 plus `order` terms B_2k / (2k (2k - 1) x^(2k - 1)).
+// The series is asymptotic, so past a handful of terms it only helps for large x.
+pub fn stirling_series(order: u64) -> Function {
+    let x = Function::X;
+    let bernoulli = bernoulli_numbers(2 * order as usize);
+    let mut series = (&x - 0.5) * x.ln() - &x + (2.0 * std::f64::consts::PI).ln() / 2.0;
+    for k in 1..= order as usize {
+        let n = 2 * k;
+        series = series + bernoulli[n] / (n * (n - 1)) as f64 * x.powf(1.0 - n as f64);
+    }
+    series
+}
+
+fn bernoulli_numbers(max: usize) -> Vec<f64> {
+    let mut numbers = vec![1.0];
+    for m in 1..= max {
+        let mut binomial = 1.0;
+        let mut sum = 0.0;
+        for (k, b) in numbers.iter().enumerate() {
+            sum += binomial * b;
+            binomial = binomial * (m + 1 - k) as f64 / (k + 1) as f64;
+        }
+        numbers.push(-sum / (m + 1) as f64);
+    }
+    numbers
+}
+
+pub fn taylor_symbolic_coeffs(order: u64, center: f64, f: &Function) -> String {
+    let base = match center {
+        0.0 => "x".to_owned(),
+        c if c < 0.0 => format!("(x + {})", -c),
+        c => format!("(x - {})", c),
+    };
+    let mut series = String::new();
+    for (n, nth_derivative) in prepare_derivatives(f, order).derivatives.iter().enumerate() {
+        let value = nth_derivative.eval_shared(center);
+        if value == 0.0 {
+            continue;
+        }
+        let power = match n {
+            0 => String::new(),
+            1 => base.clone(),
+            _ => format!("{}^{}", base, n),
+        };
+        let magnitude = match (value.abs(), n) {
+            (m, 0) => format!("{}", m),
+            (1.0, _) => power,
+            (m, _) => format!("{}*{}", m, power),
+        };
+        let term = if n >= 2 { format!("{}/{}!", magnitude, n) } else { magnitude };
+        series = match (series.is_empty(), value < 0.0) {
+            (true, true) => format!("-{}", term),
+            (true, false) => term,
+            (false, true) => format!("{} - {}", series, term),
+            (false, false) => format!("{} + {}", series, term),
+        };
+    }
+    if series.is_empty() { "0".to_owned() } else { series }
 }
 
-fn factorial(n: u64) -> u64 {
-    (2..=n).product()
+const DIVERGENCE_WINDOW: usize = 4;
+
+// Growth is judged by the per-degree ratio (|c_n| / |c_m|)^(1 / (n - m)) across the last few nonzero
+// coefficients, so one jump like exp(3x)'s early terms isn't enough: the ratio has to reach 1 without
+// falling. Zero and negligible coefficients (like the odd terms of an even function) are skipped, and a
+// polynomial is never flagged since its series ends.
+pub fn taylor_until_divergence(center: f64, f: &Function, max_terms: usize) -> (Vec<f64>, bool) {
+    let polynomial = f.is_polynomial();
+    let mut builder = TaylorBuilder::new(center, f);
+    let mut nonzero: Vec<(usize, f64)> = vec![];
+    let mut previous_ratio = f64::INFINITY;
+    for n in 0..max_terms {
+        builder.next_term();
+        let magnitude = builder.coefficients[n].abs();
+        if !magnitude.is_finite() {
+            return (builder.coefficients, true);
+        }
+        if polynomial || magnitude == 0.0 || nonzero.last().is_some_and(|&(_, last)| magnitude < 1e-9 * last) {
+            continue;
+        }
+        nonzero.push((n, magnitude));
+        let Some(&(m, first)) = nonzero.len().checked_sub(DIVERGENCE_WINDOW).map(|i| &nonzero[i]) else { continue };
+        let ratio = (magnitude / first).powf(1.0 / (n - m) as f64);
+        if ratio >= 1.0 && ratio >= 0.99 * previous_ratio {
+            return (builder.coefficients, true);
+        }
+        previous_ratio = ratio;
+    }
+    (builder.coefficients, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Function = Function::X;
+
+    #[test]
+    fn series_product_matches_series_of_product() {
+        let product = product_coefficients(8, 0.0, &X.sin(), &X.cos());
+        let direct = taylor_coefficients(8, 0.0, &((2.0 * X).sin() / 2.0));
+        for (a, b) in product.iter().zip(&direct) {
+            assert!((a - b).abs() < 1e-12, "{product:?} {direct:?}");
+        }
+        assert_eq!(multiply_series(&[1.0, 1.0], &[1.0, -1.0], 2), vec![1.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn exp_error_shrinks_with_order() {
+        let errors = error_by_order(0.5, 0.0, &X.exp(), 10);
+        assert_eq!(errors.len(), 11);
+        assert!(errors.windows(2).all(|w| w[1] < w[0]), "{errors:?}");
+        assert!(errors[10] < 1e-10);
+    }
+
+    #[test]
+    fn geometric_series_error_grows_outside_its_radius() {
+        let errors = error_by_order(2.0, 0.0, &(1.0 - X).powf(-1.0), 10);
+        assert!(errors.windows(2).all(|w| w[1] > w[0]), "{errors:?}");
+    }
+
+    #[test]
+    fn prepared_derivatives_match_taylor_at_each_center() {
+        let f = X.sin() * X.exp();
+        let prepared = prepare_derivatives(&f, 6);
+        for center in [0.0, 1.5] {
+            assert_eq!(prepared.taylor(center, 6), Some(taylor(6, center, &f)));
+        }
+        assert_eq!(prepared.taylor(1.5, 3), Some(taylor(3, 1.5, &f)));
+        assert_eq!(prepared.taylor(1.5, 7), None);
+    }
+
+    #[test]
+    fn piecewise_taylor_tracks_sin_over_a_wide_interval() {
+        let approximation = piecewise_taylor(&X.sin(), -10.0, 10.0, 10, 8);
+        assert_eq!(approximation.pieces().len(), 10);
+        for k in 0..=400 {
+            let x = -10.0 + k as f64 / 20.0;
+            assert!((approximation.eval(x) - x.sin()).abs() < 1e-5, "at {x}");
+        }
+    }
+
+    #[test]
+    fn partial_sums_add_one_term_at_a_time() {
+        let f = X.exp();
+        let sums = taylor_partial_sums(6, 0.0, &f);
+        assert_eq!(sums.len(), 7);
+        for n in 1..sums.len() {
+            let added = (&sums[n] - &sums[n - 1]).polynomial_coefficients().unwrap();
+            assert_eq!(added.len(), n + 1);
+            assert!(added[..n].iter().all(|&c| c.abs() < 1e-15), "{added:?}");
+            assert!((added[n] - 1.0 / factorial(n as u64)).abs() < 1e-15);
+        }
+        assert_eq!(sums[6], taylor(6, 0.0, &f).simplify());
+        assert!((sums[6].eval(0.7) - taylor(6, 0.0, &f).eval(0.7)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn composed_series_matches_direct_expansion() {
+        let exp_series: Vec<f64> = (0..=6).map(|n| 1.0 / factorial(n)).collect();
+        let composed = compose_series(&exp_series, &X.sin(), 6);
+        let direct = taylor_coefficients(6, 0.0, &X.sin().exp());
+        for (a, b) in composed.iter().zip(&direct) {
+            assert!((a - b).abs() < 1e-12, "{composed:?} {direct:?}");
+        }
+    }
+
+    #[test]
+    fn symbolic_coefficients_keep_factorials() {
+        let series = taylor_symbolic_coeffs(3, 0.0, &X.exp());
+        assert!(series.contains("/2!") && series.contains("/3!"), "{series}");
+        assert_eq!(series, "1 + x + x^2/2! + x^3/3!");
+        assert_eq!(taylor_symbolic_coeffs(4, 0.0, &X.cos()), "1 - x^2/2! + x^4/4!");
+        assert_eq!(taylor_symbolic_coeffs(2, 1.0, &X.powf(2.0)), "1 + 2*(x - 1) + 2*(x - 1)^2/2!");
+        assert_eq!(taylor_symbolic_coeffs(2, -1.0, &X.powf(2.0)), "1 - 2*(x + 1) + 2*(x + 1)^2/2!");
+    }
+
+    #[test]
+    fn divergence_is_flagged_for_a_finite_radius() {
+        assert!(taylor_until_divergence(0.0, &(1.0 / (1.0 + X.powf(2.0))), 16).1);
+        assert!(taylor_until_divergence(0.0, &(1.0 - 2.0 * X).powf(-1.0), 16).1);
+        assert!(!taylor_until_divergence(0.0, &X.exp(), 16).1);
+        assert!(!taylor_until_divergence(0.0, &(3.0 * X).exp(), 16).1);
+    }
+
+    #[test]
+    fn leading_zeros_and_polynomials_are_not_divergent() {
+        for f in [X.cos() - 1.0, X * X.sin(), 2.0 * X + 3.0 * X.powf(2.0), 1.0 + 2.0 * X + 3.0 * X.powf(2.0) + 4.0 * X.powf(3.0)] {
+            let (coefficients, divergent) = taylor_until_divergence(0.0, &f, 12);
+            assert!(!divergent, "{f}: {coefficients:?}");
+            assert_eq!(coefficients.len(), 12);
+        }
+    }
+
+    #[test]
+    fn atan_series_alternates_over_odd_powers() {
+        let coefficients = taylor_coefficients(7, 0.0, &X.atan());
+        let expected = [0.0, 1.0, 0.0, -1.0 / 3.0, 0.0, 1.0 / 5.0, 0.0, -1.0 / 7.0];
+        for (n, (c, e)) in coefficients.iter().zip(expected).enumerate() {
+            assert!((c - e).abs() < 1e-12, "coefficient {n} is {c}");
+        }
+    }
+
+    #[test]
+    fn sinh_series_has_the_odd_factorials() {
+        let coefficients = taylor_coefficients(5, 0.0, &X.sinh());
+        let expected = [0.0, 1.0, 0.0, 1.0 / 6.0, 0.0, 1.0 / 120.0];
+        for (n, (c, e)) in coefficients.iter().zip(expected).enumerate() {
+            assert!((c - e).abs() < 1e-12, "coefficient {n} is {c}");
+        }
+        let series = taylor(5, 0.0, &X.sinh());
+        let x = 0.3f64;
+        assert!((series.eval(x) - (x + x.powi(3) / 6.0 + x.powi(5) / 120.0)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn accuracy_table_converges_for_exp() {
+        let table = taylor_accuracy_table(0.0, &X.exp(), 1.0, 12);
+        assert_eq!(table.len(), 13);
+        assert!(table.iter().all(|row| row.2 == table[0].2));
+        assert!((table[0].2 - std::f64::consts::E).abs() < 1e-15);
+        for window in table.windows(2) {
+            assert!(window[1].3 < window[0].3, "order {}", window[1].0);
+        }
+        assert!(table[12].3 < 1e-9);
+    }
+
+    #[test]
+    fn reciprocal_series_of_cos_is_the_secant_series() {
+        let series = reciprocal_series(0.0, &X.cos(), 4).unwrap();
+        let expected = [1.0, 0.0, 0.5, 0.0, 5.0 / 24.0];
+        for (n, (c, e)) in taylor_coefficients(4, 0.0, &series).iter().zip(expected).enumerate() {
+            assert!((c - e).abs() < 1e-12, "coefficient {n} is {c}");
+        }
+        assert_eq!(reciprocal_series(0.0, &X.sin(), 4), None);
+    }
+
+    #[test]
+    fn exp_error_stays_under_the_lagrange_bound() {
+        let f = X.exp();
+        for order in 0..10 {
+            let series = taylor(order, 0.0, &f);
+            for x in [0.2f64, 0.5, 1.0, -1.0] {
+                // every derivative of exp is exp, which is largest at the right end of [0, x]
+                let bound = taylor_remainder_bound(order, 0.0, x, &f, x.max(0.0).exp());
+                let error = (series.eval(x) - x.exp()).abs();
+                assert!(error <= bound, "order {order} at {x}: {error} > {bound}");
+            }
+        }
+        assert_eq!(taylor_remainder_bound(2, 0.0, 2.0, &f, 3.0), 3.0 * 8.0 / factorial(3));
+    }
+
+    #[test]
+    fn horner_polynomial_matches_the_symbolic_series() {
+        for (f, center) in [(X.exp(), 0.5), (X.sin(), 0.0), (X.cos().powf(2.0), 1.0)] {
+            let polynomial = taylor_polynomial(50, center, &f);
+            let symbolic = taylor(50, center, &f);
+            for x in [-1.0, 0.0, 0.7, 2.0] {
+                let (horner, tree) = (polynomial.eval(x), symbolic.eval(x));
+                assert!((horner - tree).abs() <= 1e-12 * tree.abs().max(1.0), "{f} at {x}: {horner} vs {tree}");
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "timing comparison; run with --release -- --ignored --nocapture"]
+    fn horner_evaluation_is_faster_than_the_tree() {
+        let f = X.sin();
+        let (polynomial, symbolic) = (taylor_polynomial(50, 0.0, &f), taylor(50, 0.0, &f));
+        let xs: Vec<f64> = (0..100_000).map(|k| k as f64 / 100_000.0).collect();
+        let start = std::time::Instant::now();
+        let horner: f64 = xs.iter().map(|&x| polynomial.eval(x)).sum();
+        let horner_time = start.elapsed();
+        let start = std::time::Instant::now();
+        let tree: f64 = xs.iter().map(|&x| symbolic.eval(x)).sum();
+        let tree_time = start.elapsed();
+        println!("order 50 over {} points: Horner {horner_time:?}, tree {tree_time:?}", xs.len());
+        assert!((horner - tree).abs() < 1e-6 * tree.abs());
+        assert!(horner_time < tree_time);
+    }
+
+    #[test]
+    fn stirling_series_approaches_log_gamma() {
+        // ln Γ(n) = ln((n - 1)!)
+        let log_gamma = |n: u64| (1..n).map(|k| (k as f64).ln()).sum::<f64>();
+        for n in [10, 20, 50] {
+            let exact = log_gamma(n);
+            let errors: Vec<f64> = (0..4).map(|order| (stirling_series(order).eval(n as f64) - exact).abs()).collect();
+            assert!(errors.windows(2).all(|pair| pair[1] < pair[0]), "{n}: {errors:?}");
+            assert!(errors[3] < 1e-9 * exact, "{n}: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn exp_series_agrees_in_both_precisions() {
+        let single = taylor(4, 0.0f32, &Function::<f32>::X.exp());
+        let double = taylor(4, 0.0, &X.exp());
+        let coefficients = TaylorBuilder::with_order(0.0f32, &Function::<f32>::X.exp(), 4).coefficients().to_vec();
+        assert_eq!(coefficients, taylor_coefficients(4, 0.0, &X.exp()).iter().map(|&c| c as f32).collect::<Vec<_>>());
+        for x in [-0.5, 0.1, 1.0] {
+            let expected = 1.0 + x + x * x / 2.0 + x * x * x / 6.0 + x * x * x * x / 24.0;
+            assert!((double.eval(x) - expected).abs() < 1e-15);
+            assert!((single.eval(x as f32) as f64 - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn series_display_subtracts_negative_terms() {
+        assert_eq!(taylor(4, 0.0, &X.cos()).to_string(), "1 - 0.5 * x ^ 2 + 0.041666666666666664 * x ^ 4");
+        assert_eq!(taylor(4, 0.0, &(1.0 / (1.0 + X))).to_string(), "1 - x + x ^ 2 - x ^ 3 + x ^ 4");
+        assert_eq!(
+            taylor(7, 0.0, &X.sin()).to_string(),
+            "x - 0.16666666666666666 * x ^ 3 + 0.008333333333333333 * x ^ 5 - 0.0001984126984126984 * x ^ 7"
+        );
+        assert_eq!(taylor(3, 1.0, &X.ln()).to_string(), "x - 1 - 0.5 * (x - 1) ^ 2 + 0.3333333333333333 * (x - 1) ^ 3");
+        assert_eq!(taylor_polynomial(4, 0.0, &X.cos()).to_function(), taylor(4, 0.0, &X.cos()));
+    }
+
+    #[test]
+    fn high_order_coefficients_stay_finite() {
+        let coefficients = taylor_coefficients(25, 0.0, &X.exp());
+        assert!((coefficients[25] * factorial(25) - 1.0).abs() < 1e-12, "{}", coefficients[25]);
+        assert!(coefficients[25] > 0.0 && coefficients[25] < 1e-25);
+        let series = taylor(25, 0.0, &X.exp());
+        assert!((series.eval(2.0) - 2f64.exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn erf_series_matches_the_known_coefficients() {
+        let scale = 2.0 / std::f64::consts::PI.sqrt();
+        let expected = [0.0, 1.0, 0.0, -1.0 / 3.0, 0.0, 1.0 / 10.0, 0.0, -1.0 / 42.0].map(|c| scale * c);
+        for (n, (c, e)) in taylor_coefficients(7, 0.0, &X.erf()).iter().zip(expected).enumerate() {
+            assert!((c - e).abs() < 1e-12, "coefficient {n} is {c}");
+        }
+        assert!((X.erf().eval(0.5) - 0.5204998778130465).abs() < 1e-15);
+    }
 }